@@ -0,0 +1,65 @@
+// Compares GridWorld::map with GridWorld::par_map by repeatedly applying day 4's
+// roll-removal rule until the grid stops changing.
+//
+// Usage: cargo bench --features parallel --bench par_map [-- filename]
+use std::time::{Duration, Instant};
+
+use advent2025::{
+    grid::GridCharWorld,
+    multidim::{Dir, DirType, Position},
+};
+use enum_iterator::all;
+
+const REPETITIONS: usize = 10;
+
+fn main() -> anyhow::Result<()> {
+    let filename = std::env::args()
+        .skip(1)
+        .find(|a| !a.starts_with("-"))
+        .unwrap_or("ex/day4.txt".to_string());
+    let world = GridCharWorld::from_char_file(filename.as_str())?;
+
+    let (sequential, sequential_time) =
+        time(|| remove_all(&world, |w| w.map(|p, v| step(w, p, *v))));
+    let (parallel, parallel_time) =
+        time(|| remove_all(&world, |w| w.par_map(|p, v| step(w, p, *v))));
+    assert_eq!(sequential, parallel);
+
+    println!("{filename}: {} x {}", world.width(), world.height());
+    println!("map:     {} us", sequential_time.as_micros());
+    println!("par_map: {} us", parallel_time.as_micros());
+    Ok(())
+}
+
+fn time<F: Fn() -> GridCharWorld>(f: F) -> (GridCharWorld, Duration) {
+    let start = Instant::now();
+    let mut result = f();
+    for _ in 1..REPETITIONS {
+        result = f();
+    }
+    (
+        result,
+        Instant::now().duration_since(start) / REPETITIONS as u32,
+    )
+}
+
+fn remove_all<M: Fn(&GridCharWorld) -> GridCharWorld>(
+    world: &GridCharWorld,
+    mapper: M,
+) -> GridCharWorld {
+    let mut current = world.clone();
+    loop {
+        let updated = mapper(&current);
+        if updated == current {
+            return current;
+        }
+        current = updated;
+    }
+}
+
+fn step(world: &GridCharWorld, p: Position, v: char) -> char {
+    let rolls = all::<Dir>()
+        .filter(|d| world.value(d.neighbor(p)) == Some('@'))
+        .count();
+    if v == '@' && rolls < 4 { '.' } else { v }
+}
@@ -0,0 +1,153 @@
+use bare_metal_modulo::NumType;
+
+use crate::multidim::Point;
+
+/// An axis-aligned box of integer points, including both corners.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct AaBox<N: NumType + Default, const S: usize> {
+    min: Point<N, S>,
+    max: Point<N, S>,
+}
+
+impl<N: NumType + Default, const S: usize> AaBox<N, S> {
+    /// The box spanned by two opposite corners, in any order.
+    pub fn new(a: Point<N, S>, b: Point<N, S>) -> Self {
+        Self::from_points([a, b].into_iter()).unwrap()
+    }
+
+    /// The smallest box containing every point, or `None` if there are none.
+    pub fn from_points<I: Iterator<Item = Point<N, S>>>(points: I) -> Option<Self> {
+        Point::min_max_points(points).map(|(min, max)| Self { min, max })
+    }
+
+    pub fn min(&self) -> Point<N, S> {
+        self.min
+    }
+
+    pub fn max(&self) -> Point<N, S> {
+        self.max
+    }
+
+    pub fn corners(&self) -> Vec<Point<N, S>> {
+        Point::bounding_box([self.min, self.max].into_iter()).unwrap()
+    }
+
+    /// The number of integer points in the box.
+    pub fn volume(&self) -> N {
+        (0..S).fold(N::one(), |v, i| v * (self.max[i] - self.min[i] + N::one()))
+    }
+
+    pub fn contains(&self, p: &Point<N, S>) -> bool {
+        (0..S).all(|i| self.min[i] <= p[i] && p[i] <= self.max[i])
+    }
+
+    pub fn contains_box(&self, other: &Self) -> bool {
+        self.contains(&other.min) && self.contains(&other.max)
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let min = Point::from_iter((0..S).map(|i| self.min[i].max(other.min[i])));
+        let max = Point::from_iter((0..S).map(|i| self.max[i].min(other.max[i])));
+        (0..S)
+            .all(|i| min[i] <= max[i])
+            .then_some(Self { min, max })
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// Disjoint boxes covering exactly the points of `self` that are not in `other`.
+    /// At most `2 * S` boxes result, sliced off one axis at a time.
+    pub fn subtract(&self, other: &Self) -> Vec<Self> {
+        let Some(overlap) = self.intersection(other) else {
+            return vec![*self];
+        };
+        let mut result = vec![];
+        let mut remaining = *self;
+        for i in 0..S {
+            if remaining.min[i] < overlap.min[i] {
+                let mut below = remaining;
+                below.max[i] = overlap.min[i] - N::one();
+                result.push(below);
+                remaining.min[i] = overlap.min[i];
+            }
+            if remaining.max[i] > overlap.max[i] {
+                let mut above = remaining;
+                above.min[i] = overlap.max[i] + N::one();
+                result.push(above);
+                remaining.max[i] = overlap.max[i];
+            }
+        }
+        result
+    }
+
+    /// The number of integer points covered by at least one of `boxes`.
+    pub fn union_volume<'a, I: IntoIterator<Item = &'a Self>>(boxes: I) -> N
+    where
+        N: 'a,
+    {
+        let mut disjoint: Vec<Self> = vec![];
+        for b in boxes {
+            let mut pieces = vec![*b];
+            for existing in disjoint.iter() {
+                pieces = pieces
+                    .iter()
+                    .flat_map(|piece| piece.subtract(existing))
+                    .collect();
+            }
+            disjoint.extend(pieces);
+        }
+        disjoint.iter().fold(N::zero(), |v, b| v + b.volume())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::multidim::Point;
+
+    use super::AaBox;
+
+    fn cube(lo: i64, hi: i64) -> AaBox<i64, 3> {
+        AaBox::new(Point::new([lo, lo, lo]), Point::new([hi, hi, hi]))
+    }
+
+    #[test]
+    fn test_basics() {
+        let b = AaBox::new(Point::new([5, -1]), Point::new([2, 3]));
+        assert_eq!(b.min(), Point::new([2, -1]));
+        assert_eq!(b.max(), Point::new([5, 3]));
+        assert_eq!(b.volume(), 20);
+        assert_eq!(b.corners().len(), 4);
+        assert!(b.contains(&Point::new([2, 3])));
+        assert!(!b.contains(&Point::new([6, 0])));
+
+        let other = AaBox::new(Point::new([4, 3]), Point::new([9, 9]));
+        assert_eq!(
+            b.intersection(&other),
+            Some(AaBox::new(Point::new([4, 3]), Point::new([5, 3])))
+        );
+        assert!(!b.intersects(&AaBox::new(Point::new([6, 0]), Point::new([7, 0]))));
+        assert!(b.contains_box(&AaBox::new(Point::new([3, 0]), Point::new([4, 1]))));
+    }
+
+    #[test]
+    fn test_reactor() {
+        let mut on: Vec<AaBox<i64, 3>> = vec![];
+        let steps = [
+            (true, cube(10, 12), 27),
+            (true, cube(11, 13), 46),
+            (false, cube(9, 11), 38),
+            (true, cube(10, 10), 39),
+        ];
+        for (turn_on, step, lit) in steps {
+            on = on.iter().flat_map(|b| b.subtract(&step)).collect();
+            if turn_on {
+                on.push(step);
+            }
+            assert_eq!(on.iter().map(|b| b.volume()).sum::<i64>(), lit);
+        }
+        assert_eq!(AaBox::union_volume(&[cube(10, 12), cube(11, 13)]), 46);
+        assert_eq!(AaBox::union_volume(&[cube(0, 1), cube(0, 1)]), 8);
+    }
+}
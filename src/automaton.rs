@@ -0,0 +1,186 @@
+use std::marker::PhantomData;
+
+use enum_iterator::all;
+
+use crate::multidim::{Dir, DirType, ManhattanDir, Position};
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum UpdateMode {
+    /// Every cell's new value is computed from the previous generation.
+    Synchronous,
+    /// Cells are updated one at a time, so later cells see earlier updates.
+    InPlace,
+}
+
+pub trait AutomatonGrid<V: Copy> {
+    fn cell(&self, p: Position) -> Option<V>;
+
+    fn set_cell(&mut self, p: Position, value: V);
+
+    /// Positions that might change in the next generation.
+    fn candidates(&self) -> Vec<Position>;
+
+    fn neighborhood(&self, p: Position) -> Neighborhood<V> {
+        Neighborhood {
+            values: all::<Dir>().map(|d| self.cell(d.neighbor(p))).collect(),
+        }
+    }
+}
+
+/// Values of the eight cells surrounding a position, in `Dir` order.
+/// Neighbors outside a bounded grid are `None`.
+#[derive(Clone, Debug)]
+pub struct Neighborhood<V: Copy> {
+    values: Vec<Option<V>>,
+}
+
+impl<V: Copy + Eq> Neighborhood<V> {
+    pub fn get(&self, d: Dir) -> Option<V> {
+        self.values[d as usize]
+    }
+
+    pub fn count(&self, value: V) -> usize {
+        self.count_where(|v| v == value)
+    }
+
+    pub fn count_where<P: Fn(V) -> bool>(&self, predicate: P) -> usize {
+        self.values
+            .iter()
+            .flatten()
+            .filter(|v| predicate(**v))
+            .count()
+    }
+
+    pub fn manhattan_count(&self, value: V) -> usize {
+        all::<ManhattanDir>()
+            .filter(|d| self.get(Dir::from(*d)) == Some(value))
+            .count()
+    }
+}
+
+pub struct Automaton<V: Copy, R: Fn(Position, V, &Neighborhood<V>) -> V> {
+    mode: UpdateMode,
+    rule: R,
+    _value: PhantomData<V>,
+}
+
+impl<V: Copy + Eq, R: Fn(Position, V, &Neighborhood<V>) -> V> Automaton<V, R> {
+    pub fn new(mode: UpdateMode, rule: R) -> Self {
+        Self {
+            mode,
+            rule,
+            _value: PhantomData,
+        }
+    }
+
+    /// Advances `grid` by one generation, returning the number of cells that changed.
+    pub fn step<G: AutomatonGrid<V>>(&self, grid: &mut G) -> usize {
+        match self.mode {
+            UpdateMode::Synchronous => {
+                let changes = grid
+                    .candidates()
+                    .into_iter()
+                    .filter_map(|p| {
+                        grid.cell(p).and_then(|v| {
+                            let updated = (self.rule)(p, v, &grid.neighborhood(p));
+                            if updated == v {
+                                None
+                            } else {
+                                Some((p, updated))
+                            }
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                for (p, v) in changes.iter() {
+                    grid.set_cell(*p, *v);
+                }
+                changes.len()
+            }
+            UpdateMode::InPlace => {
+                let mut changed = 0;
+                for p in grid.candidates() {
+                    if let Some(v) = grid.cell(p) {
+                        let updated = (self.rule)(p, v, &grid.neighborhood(p));
+                        if updated != v {
+                            grid.set_cell(p, updated);
+                            changed += 1;
+                        }
+                    }
+                }
+                changed
+            }
+        }
+    }
+
+    /// Runs `generations` steps, returning the number of changed cells for each.
+    pub fn run<G: AutomatonGrid<V>>(&self, grid: &mut G, generations: usize) -> Vec<usize> {
+        (0..generations).map(|_| self.step(grid)).collect()
+    }
+
+    /// Runs until a generation changes nothing. The returned counts omit that final
+    /// generation, so its length is the number of generations that made progress.
+    pub fn run_until_stable<G: AutomatonGrid<V>>(&self, grid: &mut G) -> Vec<usize> {
+        let mut result = vec![];
+        loop {
+            let changed = self.step(grid);
+            if changed == 0 {
+                return result;
+            }
+            result.push(changed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        grid::{GridCharWorld, InfiniteGrid},
+        multidim::Position,
+    };
+
+    use super::{Automaton, Neighborhood, UpdateMode};
+
+    const ROLLS: &str = "..@@.@@@@.
+@@@.@.@.@@
+@@@@@.@.@@
+@.@@@@..@.
+@@.@@@@.@@
+.@@@@@@@.@
+.@.@.@.@@@
+@.@@@.@@@@
+.@@@@@@@@.
+@.@.@@@.@.";
+
+    fn remove_roll(_: Position, v: char, n: &Neighborhood<char>) -> char {
+        if v == '@' && n.count('@') < 4 { '.' } else { v }
+    }
+
+    #[test]
+    fn test_rolls() {
+        for mode in [UpdateMode::Synchronous, UpdateMode::InPlace] {
+            let mut world = ROLLS.parse::<GridCharWorld>().unwrap();
+            let removed = Automaton::new(mode, remove_roll).run_until_stable(&mut world);
+            assert_eq!(removed.iter().sum::<usize>(), 43);
+        }
+        let mut world = ROLLS.parse::<GridCharWorld>().unwrap();
+        let automaton = Automaton::new(UpdateMode::Synchronous, remove_roll);
+        assert_eq!(automaton.step(&mut world), 13);
+    }
+
+    #[test]
+    fn test_blinker() {
+        let mut grid = InfiniteGrid::default();
+        for x in 0..3 {
+            grid.add(x, 0, true);
+        }
+        let life = Automaton::new(UpdateMode::Synchronous, |_, alive, n| {
+            let live = n.count(true);
+            live == 3 || (alive && live == 2)
+        });
+        assert_eq!(life.run(&mut grid, 1), vec![4]);
+        assert!((-1..=1).all(|y| grid.get(1, y)));
+        assert!(!grid.get(0, 0) && !grid.get(2, 0));
+        assert_eq!(life.run(&mut grid, 3), vec![4, 4, 4]);
+        assert!((0..3).all(|x| grid.get(x, 0)));
+    }
+}
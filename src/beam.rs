@@ -0,0 +1,279 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::{
+    grid::GridCharWorld,
+    multidim::{DirType, ManhattanDir, Position},
+};
+
+/// A beam occupying a cell and traveling in a direction.
+pub type BeamState = (Position, ManhattanDir);
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TileBehavior {
+    /// Beams continue in the same direction.
+    Pass,
+    /// Like `/`: eastbound beams turn north, southbound turn west, and so on.
+    MirrorSlash,
+    /// Like `\`: eastbound beams turn south, northbound turn west, and so on.
+    MirrorBackslash,
+    /// Like `|`: horizontal beams split north and south; vertical beams pass.
+    SplitVertical,
+    /// Like `-`: vertical beams split east and west; horizontal beams pass.
+    SplitHorizontal,
+    /// Beams leave through both neighboring cells perpendicular to their travel,
+    /// still moving in their original direction.
+    SideSplit,
+    /// Beams stop here.
+    Absorb,
+}
+
+impl TileBehavior {
+    pub fn outgoing(&self, (p, d): BeamState) -> Vec<BeamState> {
+        let toward = |d: ManhattanDir| (d.neighbor(p), d);
+        let horizontal = d == ManhattanDir::E || d == ManhattanDir::W;
+        match self {
+            Self::Pass => vec![toward(d)],
+            Self::MirrorSlash => vec![toward(if horizontal {
+                d.counterclockwise()
+            } else {
+                d.clockwise()
+            })],
+            Self::MirrorBackslash => vec![toward(if horizontal {
+                d.clockwise()
+            } else {
+                d.counterclockwise()
+            })],
+            Self::SplitVertical if horizontal => {
+                vec![toward(ManhattanDir::N), toward(ManhattanDir::S)]
+            }
+            Self::SplitHorizontal if !horizontal => {
+                vec![toward(ManhattanDir::E), toward(ManhattanDir::W)]
+            }
+            Self::SplitVertical | Self::SplitHorizontal => vec![toward(d)],
+            Self::SideSplit => vec![
+                (d.counterclockwise().neighbor(p), d),
+                (d.clockwise().neighbor(p), d),
+            ],
+            Self::Absorb => vec![],
+        }
+    }
+}
+
+pub struct BeamTracer<'a> {
+    world: &'a GridCharWorld,
+    tiles: HashMap<char, TileBehavior>,
+}
+
+impl<'a> BeamTracer<'a> {
+    /// Every character behaves as `TileBehavior::Pass` until assigned otherwise.
+    pub fn new(world: &'a GridCharWorld) -> Self {
+        Self {
+            world,
+            tiles: HashMap::new(),
+        }
+    }
+
+    /// The usual mirror and splitter characters: `/`, `\`, `|`, `-`.
+    pub fn with_mirrors(world: &'a GridCharWorld) -> Self {
+        let mut result = Self::new(world);
+        result.set_tile('/', TileBehavior::MirrorSlash);
+        result.set_tile('\\', TileBehavior::MirrorBackslash);
+        result.set_tile('|', TileBehavior::SplitVertical);
+        result.set_tile('-', TileBehavior::SplitHorizontal);
+        result
+    }
+
+    pub fn set_tile(&mut self, tile: char, behavior: TileBehavior) {
+        self.tiles.insert(tile, behavior);
+    }
+
+    pub fn behavior_at(&self, p: Position) -> TileBehavior {
+        self.world
+            .value(p)
+            .and_then(|c| self.tiles.get(&c).copied())
+            .unwrap_or(TileBehavior::Pass)
+    }
+
+    /// Beam states reachable in one step from `state` that remain inside the grid.
+    pub fn successors(&self, state: BeamState) -> Vec<BeamState> {
+        self.behavior_at(state.0)
+            .outgoing(state)
+            .into_iter()
+            .filter(|(p, _)| self.world.in_bounds(*p))
+            .collect()
+    }
+
+    /// Follows every beam from `start` until it leaves the grid, is absorbed, or
+    /// reaches a state that has already been visited. The search is depth-first, so
+    /// a beam has looped exactly when it reaches a state on its own current path.
+    pub fn trace(&self, start: BeamState) -> BeamTrace {
+        let mut states = BTreeSet::new();
+        let mut on_path = HashSet::new();
+        let mut looped = false;
+        let mut stack = vec![(start, false)];
+        while let Some((state, expanded)) = stack.pop() {
+            if expanded {
+                on_path.remove(&state);
+            } else if states.insert(state) {
+                on_path.insert(state);
+                stack.push((state, true));
+                for successor in self.successors(state) {
+                    if on_path.contains(&successor) {
+                        looped = true;
+                    } else if !states.contains(&successor) {
+                        stack.push((successor, false));
+                    }
+                }
+            }
+        }
+        BeamTrace { states, looped }
+    }
+
+    /// The number of distinct beam paths from `start` that end by leaving the grid or
+    /// being absorbed, counting each split as a separate path. Returns `None` when a
+    /// beam can cycle, since the count would then be unbounded.
+    pub fn path_count(&self, start: BeamState) -> Option<u64> {
+        let mut counts: HashMap<BeamState, u64> = HashMap::new();
+        let mut on_path = HashSet::new();
+        let mut stack = vec![(start, false)];
+        while let Some((state, expanded)) = stack.pop() {
+            if expanded {
+                on_path.remove(&state);
+                let outgoing = self.behavior_at(state.0).outgoing(state);
+                let count = if outgoing.is_empty() {
+                    1
+                } else {
+                    outgoing
+                        .iter()
+                        .map(|s| {
+                            if self.world.in_bounds(s.0) {
+                                counts[s]
+                            } else {
+                                1
+                            }
+                        })
+                        .fold(0, u64::saturating_add)
+                };
+                counts.insert(state, count);
+            } else if !counts.contains_key(&state) {
+                on_path.insert(state);
+                stack.push((state, true));
+                for successor in self.successors(state) {
+                    if on_path.contains(&successor) {
+                        return None;
+                    }
+                    if !counts.contains_key(&successor) {
+                        stack.push((successor, false));
+                    }
+                }
+            }
+        }
+        counts.get(&start).copied()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BeamTrace {
+    states: BTreeSet<BeamState>,
+    looped: bool,
+}
+
+impl BeamTrace {
+    pub fn states(&self) -> &BTreeSet<BeamState> {
+        &self.states
+    }
+
+    /// Whether some beam returns to a state it has already passed through.
+    pub fn looped(&self) -> bool {
+        self.looped
+    }
+
+    pub fn energized(&self) -> BTreeSet<Position> {
+        self.states.iter().map(|(p, _)| *p).collect()
+    }
+
+    pub fn num_energized(&self) -> usize {
+        self.energized().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        grid::GridCharWorld,
+        multidim::{ManhattanDir, Position},
+    };
+
+    use super::{BeamTracer, TileBehavior};
+
+    const CONTRAPTION: &str = r".|...\....
+|.-.\.....
+.....|-...
+........|.
+..........
+.........\
+..../.\\..
+.-.-/..|..
+.|....-|.\
+..//.|....";
+
+    const MANIFOLD: &str = ".......S.......
+...............
+.......^.......
+...............
+......^.^......
+...............
+.....^.^.^.....
+...............
+....^.^...^....
+...............
+...^.^...^.^...
+...............
+..^...^.....^..
+...............
+.^.^.^.^.^...^.
+...............";
+
+    #[test]
+    fn test_mirrors() {
+        let world = CONTRAPTION.parse::<GridCharWorld>().unwrap();
+        let tracer = BeamTracer::with_mirrors(&world);
+        let trace = tracer.trace((Position::default(), ManhattanDir::E));
+        assert_eq!(trace.num_energized(), 46);
+        assert!(trace.looped());
+        assert_eq!(
+            tracer.path_count((Position::default(), ManhattanDir::E)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_side_splitters() {
+        let world = MANIFOLD.parse::<GridCharWorld>().unwrap();
+        let mut tracer = BeamTracer::new(&world);
+        tracer.set_tile('^', TileBehavior::SideSplit);
+        let start = (world.any_position_for('S'), ManhattanDir::S);
+        let trace = tracer.trace(start);
+        assert!(!trace.looped());
+        let splits = trace
+            .energized()
+            .iter()
+            .filter(|p| world.value(**p) == Some('^'))
+            .count();
+        assert_eq!(splits, 21);
+        assert_eq!(tracer.path_count(start), Some(40));
+    }
+
+    #[test]
+    fn test_absorb() {
+        let world = "..#..".parse::<GridCharWorld>().unwrap();
+        let mut tracer = BeamTracer::new(&world);
+        tracer.set_tile('#', TileBehavior::Absorb);
+        let trace = tracer.trace((Position::default(), ManhattanDir::E));
+        assert_eq!(trace.num_energized(), 3);
+        assert_eq!(
+            tracer.path_count((Position::default(), ManhattanDir::E)),
+            Some(1)
+        );
+    }
+}
@@ -1,28 +1,50 @@
 use std::{cmp::max, fmt::Display, ops::BitXor, str::FromStr};
 
-use advent2025::{Part, advent_main, all_lines, search_iter::BfsIter};
+use advent2025::{
+    Part, advent_main, all_lines,
+    gf2::{Gf2Matrix, Gf2Vector},
+    ilp::{IntProgram, LinearExpr},
+    search_iter::BfsIter,
+};
 use anyhow::bail;
 use itertools::Itertools;
+#[cfg(feature = "z3")]
 use z3::{Optimize, SatResult, ast::Int};
 
 fn main() -> anyhow::Result<()> {
-    advent_main(|filename, part, _| {
+    advent_main(|filename, part, options| {
         let machines = all_lines(filename)?
             .map(|line| line.parse::<MachineSpec>().unwrap())
             .collect_vec();
         MachineSpec::assert_valid(&machines, filename)?;
-        let score = machines
-            .iter()
-            .map(|m| match part {
-                Part::One => m.min_button_presses_indicator_lights(),
-                Part::Two => m.min_button_presses_joltage(),
-            })
-            .sum::<u64>();
+        let backend = if options.contains(&"-z3") {
+            Backend::Z3
+        } else if options.contains(&"-compare") {
+            Backend::Compare
+        } else {
+            Backend::Native
+        };
+        let mut score = 0;
+        for m in machines.iter() {
+            score += match part {
+                Part::One => m.min_button_presses_indicator_lights()?,
+                Part::Two => m.min_button_presses_joltage(backend)?,
+            };
+        }
         println!("{score}");
         Ok(())
     })
 }
 
+/// Which solver handles the joltage equations: the crate's own `IntProgram`, z3
+/// (requires the `z3` feature), or both, checking that they agree.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Backend {
+    Native,
+    Z3,
+    Compare,
+}
+
 #[derive(Default)]
 struct MachineSpec {
     target: Bits,
@@ -31,7 +53,22 @@ struct MachineSpec {
 }
 
 impl MachineSpec {
-    fn min_button_presses_indicator_lights(&self) -> u64 {
+    fn min_button_presses_indicator_lights(&self) -> anyhow::Result<u64> {
+        let lights = self.target.num_bits;
+        let as_vector = |bits: &Bits| (0..lights).map(|i| bits.get(i)).collect::<Gf2Vector>();
+        let columns = self.buttons.iter().map(as_vector).collect_vec();
+        let matrix = Gf2Matrix::from_columns(&columns, lights as usize);
+        match matrix.solve(&as_vector(&self.target)) {
+            Some(space) => Ok(match space.min_weight_solution() {
+                Some(presses) => presses.count_ones() as u64,
+                // Too many free buttons to try them all, so search the light patterns.
+                None => self.min_button_presses_bfs(),
+            }),
+            None => bail!("Unsolvable: {self}"),
+        }
+    }
+
+    fn min_button_presses_bfs(&self) -> u64 {
         let mut iter = BfsIter::new(Bits::default(), |s| self.successors_indicator_lights(s));
         let found = iter.by_ref().find(|b| b.bits == self.target.bits).unwrap();
         iter.depth_for(&found) as u64
@@ -41,7 +78,52 @@ impl MachineSpec {
         self.buttons.iter().map(|button| *bits ^ *button).collect()
     }
 
-    fn min_button_presses_joltage(&self) -> u64 {
+    fn min_button_presses_joltage(&self, backend: Backend) -> anyhow::Result<u64> {
+        match backend {
+            Backend::Native => self.min_button_presses_joltage_native(),
+            Backend::Z3 => self.min_button_presses_joltage_z3(),
+            Backend::Compare => {
+                let native = self.min_button_presses_joltage_native()?;
+                let z3 = self.min_button_presses_joltage_z3()?;
+                if native != z3 {
+                    bail!("{self}: native found {native}, z3 found {z3}");
+                }
+                Ok(native)
+            }
+        }
+    }
+
+    fn min_button_presses_joltage_native(&self) -> anyhow::Result<u64> {
+        let mut program = IntProgram::new();
+        let vars = self.buttons.iter().map(|_| program.int_var()).collect_vec();
+        for var in vars.iter() {
+            program.at_least(*var, 0);
+        }
+        for i in 0..self.joltages.len() {
+            program.assert_eq(
+                self.buttons
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, b)| b.get(i as u16))
+                    .map(|(i, _)| vars[i])
+                    .sum::<LinearExpr>(),
+                self.joltages[i] as i64,
+            );
+        }
+        program.minimize(vars.iter().copied().sum::<LinearExpr>());
+        match program.solve()? {
+            Some(model) => Ok(model.objective() as u64),
+            None => bail!("Unsolvable: {self}"),
+        }
+    }
+
+    #[cfg(not(feature = "z3"))]
+    fn min_button_presses_joltage_z3(&self) -> anyhow::Result<u64> {
+        bail!("Rebuild with --features z3 to use the z3 backend")
+    }
+
+    #[cfg(feature = "z3")]
+    fn min_button_presses_joltage_z3(&self) -> anyhow::Result<u64> {
         let vars = (0..self.buttons.len())
             .map(|i| Int::fresh_const(format!("n{i}").as_str()))
             .collect_vec();
@@ -65,11 +147,12 @@ impl MachineSpec {
         match solver.check(&[]) {
             SatResult::Sat => {
                 let model = solver.get_model().unwrap();
-                vars.iter()
+                Ok(vars
+                    .iter()
                     .map(|var| model.eval(var, true).unwrap().as_u64().unwrap())
-                    .sum()
+                    .sum())
             }
-            _ => panic!("Unsolvable"),
+            _ => bail!("Unsolvable: {self}"),
         }
     }
 
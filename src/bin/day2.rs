@@ -1,10 +1,8 @@
-use std::fmt::Display;
-
-use advent2025::{Part, advent_main, all_lines, log_floor};
+use advent2025::{Part, advent_main, all_lines, interval::Interval, log_floor};
 
 fn main() -> anyhow::Result<()> {
     advent_main(|filename, part, options| {
-        let ranges = Range::from_filename(filename)?;
+        let ranges = ranges_from(filename)?;
         if options.contains(&"-range") {
             for range in ranges.iter() {
                 println!("{range}: {}", range.span());
@@ -12,7 +10,7 @@ fn main() -> anyhow::Result<()> {
         }
         let total = ranges
             .iter()
-            .map(|r| r.invalid_ids(part).iter().sum::<u64>())
+            .map(|r| invalid_ids(r, part).iter().sum::<u64>())
             .sum::<u64>();
         println!("{total}");
         Ok(())
@@ -53,46 +51,22 @@ fn has_repeating_prefix(s: &str, prefix_size: usize) -> bool {
         .all(|start| prefix == &rest[start..start + prefix.len()])
 }
 
-#[derive(Debug, Copy, Clone)]
-struct Range {
-    start: u64,
-    end: u64,
+fn ranges_from(filename: &str) -> anyhow::Result<Vec<Interval<u64>>> {
+    all_lines(filename)?
+        .next()
+        .unwrap()
+        .split(",")
+        .map(|s| s.parse())
+        .collect()
 }
 
-impl Range {
-    fn from_filename(filename: &str) -> anyhow::Result<Vec<Self>> {
-        Ok(all_lines(filename)?
-            .next()
-            .unwrap()
-            .split(",")
-            .map(|s| {
-                let mid = s.find("-").unwrap();
-                Self {
-                    start: s[..mid].parse().unwrap(),
-                    end: s[mid + 1..].parse().unwrap(),
-                }
-            })
-            .collect())
-    }
-
-    fn span(&self) -> u64 {
-        1 + self.end - self.start
-    }
-
-    fn invalid_ids(&self, part: Part) -> Vec<u64> {
-        (self.start..=self.end)
-            .filter(|n| match part {
-                Part::One => invalid_part_1(*n),
-                Part::Two => invalid_part_2(*n),
-            })
-            .collect()
-    }
-}
-
-impl Display for Range {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}-{}", self.start, self.end)
-    }
+fn invalid_ids(range: &Interval<u64>, part: Part) -> Vec<u64> {
+    (range.start()..=range.end())
+        .filter(|n| match part {
+            Part::One => invalid_part_1(*n),
+            Part::Two => invalid_part_2(*n),
+        })
+        .collect()
 }
 
 #[cfg(test)]
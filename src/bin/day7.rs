@@ -1,12 +1,17 @@
-use std::collections::BTreeSet;
-
-use advent2025::{Part, advent_main, grid::GridCharWorld, multidim::Position};
-use common_macros::b_tree_set;
-use hash_histogram::HashHistogram;
+use advent2025::{
+    Part, advent_main,
+    beam::{BeamState, BeamTracer, TileBehavior},
+    grid::GridCharWorld,
+    multidim::{ManhattanDir, Position},
+    viewer::Viewer,
+};
 
 fn main() -> anyhow::Result<()> {
     advent_main(|filename, part, options| {
         let world = GridCharWorld::from_char_file(filename)?;
+        if options.contains(&"-animate") {
+            Viewer::new(beam_frames(&world)).run();
+        }
         match part {
             Part::One => {
                 println!("{}", count_beam_splits(&world));
@@ -16,7 +21,7 @@ fn main() -> anyhow::Result<()> {
                     let trails = find_many_worlds_trails(&world);
                     println!("{}", trails.len());
                 } else {
-                    println!("{}", count_many_worlds_splits(&world));
+                    println!("{}", count_many_worlds_splits(&world).unwrap());
                 }
             }
         }
@@ -32,122 +37,64 @@ fn start(world: &GridCharWorld) -> Position {
         .unwrap()
 }
 
-fn find_splitters(world: &GridCharWorld, row: usize, beams: &BTreeSet<usize>) -> Vec<usize> {
-    beams
-        .iter()
-        .filter(|x| world.get(**x, row).unwrap() == '^')
-        .copied()
-        .collect()
+fn splitter_tracer(world: &GridCharWorld) -> BeamTracer<'_> {
+    let mut tracer = BeamTracer::new(world);
+    tracer.set_tile('^', TileBehavior::SideSplit);
+    tracer
 }
 
-fn count_beam_splits(world: &GridCharWorld) -> u64 {
-    let s = start(world);
-    let start_row = s[1] as usize;
-    let mut beams = b_tree_set![s[0] as usize];
-    let mut split_count = 0;
-    for y in start_row..(world.height() - 1) {
-        for splitter in find_splitters(world, y, &beams) {
-            split_count += 1;
-            beams.remove(&splitter);
-            beams.insert(splitter - 1);
-            beams.insert(splitter + 1);
-        }
-    }
-    split_count
+fn start_beam(world: &GridCharWorld) -> BeamState {
+    (start(world), ManhattanDir::S)
 }
 
-fn count_many_worlds_splits(world: &GridCharWorld) -> u64 {
-    let s = start(world);
-    let start_row = s[1] as usize;
-    let mut beams = b_tree_set![s[0] as usize];
-    let mut beams_through = HashHistogram::new();
-    beams_through.bump(&(s[0] as usize, s[1] as usize));
-    for y in start_row..(world.height() - 1) {
-        let splitters = find_splitters(world, y, &beams);
-        for splitter in splitters.iter() {
-            beams.remove(splitter);
-            beams.insert(splitter - 1);
-            beams.insert(splitter + 1);
-        }
-        let prev_row_keys = prev_row(&beams_through, y);
-        for key in prev_row_keys {
-            let x = key.0;
-            if splitters.contains(&x) {
-                beams_through.bump_by(&(x + 1, y), beams_through.count(&key));
-                beams_through.bump_by(&(x - 1, y), beams_through.count(&key));
-            } else {
-                beams_through.bump_by(&(x, y), beams_through.count(&key));
+fn count_beam_splits(world: &GridCharWorld) -> usize {
+    splitter_tracer(world)
+        .trace(start_beam(world))
+        .energized()
+        .iter()
+        .filter(|p| world.value(**p) == Some('^'))
+        .count()
+}
+
+fn beam_frames(world: &GridCharWorld) -> Vec<GridCharWorld> {
+    let energized = splitter_tracer(world).trace(start_beam(world)).energized();
+    let mut frame = world.clone();
+    let mut frames = vec![frame.clone()];
+    for y in (start(world)[1] + 1)..world.height() as isize {
+        for p in energized.iter().filter(|p| p[1] == y) {
+            if frame.value(*p) == Some('.') {
+                frame.update(*p, '|');
             }
         }
+        frames.push(frame.clone());
     }
-    final_row_count(world, &beams_through)
+    frames
 }
 
-fn prev_row(
-    beams_through: &HashHistogram<(usize, usize), u64>,
-    current_row: usize,
-) -> Vec<(usize, usize)> {
-    beams_through
-        .iter()
-        .filter(|((_, row), _)| *row + 1 == current_row)
-        .map(|(k, _)| *k)
-        .collect()
+fn count_many_worlds_splits(world: &GridCharWorld) -> Option<u64> {
+    splitter_tracer(world).path_count(start_beam(world))
 }
 
-fn final_row_count(
-    world: &GridCharWorld,
-    beams_through: &HashHistogram<(usize, usize), u64>,
-) -> u64 {
-    (0..world.width())
-        .map(|x| beams_through.count(&(x, world.height() - 2)))
-        .sum()
-}
-
-// Incrementally derived count_many_worlds_splits() as follows:
-// * Started with count_beam_splits()
-// * Removed split_count
-// * Added a list of lists of x positions of trails from the start
-// * Whenever you hit a splitter, add a left move to the existing trail,
-//   and add a new trail with a right move.
-// * When you don't hit a splitter, add the current x position to the end
-//   of the current trail.
-// * Then add a histogram of counts of trails passing through a specific position.
-// * Increment the histogram each time you add an x position to the end of a trail.
-// * Print the sum of counts for the final row, to ensure it equals the number of trails.
+/// Enumerates every beam path explicitly, as the x positions of the cells it passes
+/// through. Exponential in the number of splits; a cross-check for
+/// `count_many_worlds_splits()`.
 fn find_many_worlds_trails(world: &GridCharWorld) -> Vec<Vec<usize>> {
-    let s = start(world);
-    let mut trails = vec![vec![s[0] as usize]];
-    let start_row = s[1] as usize;
-    let mut beams = b_tree_set![s[0] as usize];
-    let mut beams_through = HashHistogram::new();
-    beams_through.bump(&(s[0] as usize, s[1] as usize));
-    for y in start_row..(world.height() - 1) {
-        let splitters = find_splitters(world, y, &beams);
-        for splitter in splitters.iter() {
-            beams.remove(splitter);
-            beams.insert(splitter - 1);
-            beams.insert(splitter + 1);
+    let tracer = splitter_tracer(world);
+    let mut trails = vec![];
+    let mut pending = vec![(start_beam(world), vec![])];
+    while let Some((state, mut trail)) = pending.pop() {
+        trail.push(state.0[0] as usize);
+        let outgoing = tracer.behavior_at(state.0).outgoing(state);
+        if outgoing.is_empty() {
+            trails.push(trail.clone());
         }
-        let mut new_trails = vec![];
-        for trail in trails.iter_mut() {
-            let x = trail[trail.len() - 1];
-            if splitters.contains(&x) {
-                new_trails.push(trail.clone());
-                let pushed = new_trails.len() - 1;
-                new_trails[pushed].push(x + 1);
-                beams_through.bump(&(x + 1, y));
-                trail.push(x - 1);
-                beams_through.bump(&(x - 1, y));
+        for next in outgoing {
+            if world.in_bounds(next.0) {
+                pending.push((next, trail.clone()));
             } else {
-                trail.push(x);
-                beams_through.bump(&(x, y));
+                trails.push(trail.clone());
             }
         }
-        for new_trail in new_trails {
-            trails.push(new_trail);
-        }
     }
-    let ending_counts = final_row_count(world, &beams_through);
-    println!("{ending_counts}");
     trails
 }
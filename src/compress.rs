@@ -0,0 +1,202 @@
+use std::collections::BTreeSet;
+
+use bare_metal_modulo::NumType;
+
+use crate::{
+    grid::GridWorld,
+    multidim::{Point, Position},
+};
+
+/// Maps the coordinates along one axis onto dense indices. Every distinct value gets
+/// its own block, and each gap between consecutive values becomes a single block
+/// standing in for all of the coordinates it skips.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CompressedAxis<N: NumType> {
+    starts: Vec<N>,
+}
+
+impl<N: NumType> CompressedAxis<N> {
+    pub fn new<I: IntoIterator<Item = N>>(values: I) -> Self {
+        let values = values.into_iter().collect::<BTreeSet<_>>();
+        let mut starts: Vec<N> = vec![];
+        for v in values {
+            if let Some(last) = starts.last()
+                && *last + N::one() < v
+            {
+                starts.push(*last + N::one());
+            }
+            starts.push(v);
+        }
+        Self { starts }
+    }
+
+    pub fn len(&self) -> usize {
+        self.starts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.starts.is_empty()
+    }
+
+    /// The index of the block containing `v`, or `None` if `v` lies outside the
+    /// compressed values.
+    pub fn index_of(&self, v: N) -> Option<usize> {
+        if self.is_empty() || v < self.starts[0] || v > self.starts[self.len() - 1] {
+            return None;
+        }
+        Some(match self.starts.binary_search(&v) {
+            Ok(i) => i,
+            Err(i) => i - 1,
+        })
+    }
+
+    /// The first and last original coordinates covered by block `i`.
+    pub fn range(&self, i: usize) -> Option<(N, N)> {
+        let start = *self.starts.get(i)?;
+        let end = self
+            .starts
+            .get(i + 1)
+            .map_or(start, |next| *next - N::one());
+        Some((start, end))
+    }
+
+    /// How many original coordinates block `i` covers.
+    pub fn span(&self, i: usize) -> Option<N> {
+        self.range(i).map(|(start, end)| end - start + N::one())
+    }
+}
+
+/// Compresses both axes of a set of points so that a `GridWorld` can stand in for a
+/// plane whose coordinates are too large to materialize. Each compressed cell
+/// represents a rectangle of original cells, weighted by its area.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CoordinateCompression<N: NumType> {
+    xs: CompressedAxis<N>,
+    ys: CompressedAxis<N>,
+}
+
+impl<N: NumType> CoordinateCompression<N> {
+    pub fn new(points: &[Point<N, 2>]) -> Self {
+        Self {
+            xs: CompressedAxis::new(points.iter().map(|p| p[0])),
+            ys: CompressedAxis::new(points.iter().map(|p| p[1])),
+        }
+    }
+
+    pub fn xs(&self) -> &CompressedAxis<N> {
+        &self.xs
+    }
+
+    pub fn ys(&self) -> &CompressedAxis<N> {
+        &self.ys
+    }
+
+    pub fn width(&self) -> usize {
+        self.xs.len()
+    }
+
+    pub fn height(&self) -> usize {
+        self.ys.len()
+    }
+
+    /// A compressed grid with every cell set to `default`.
+    pub fn grid<V: Copy + Clone + Eq + PartialEq>(&self, default: V) -> GridWorld<V> {
+        GridWorld::new(self.width(), self.height(), default)
+    }
+
+    /// The compressed cell containing `p`.
+    pub fn compress(&self, p: Point<N, 2>) -> Option<Position> {
+        Some(Position::from_usize(
+            self.xs.index_of(p[0])?,
+            self.ys.index_of(p[1])?,
+        ))
+    }
+
+    /// The original point at the top-left corner of compressed cell `p`.
+    pub fn original(&self, p: Position) -> Option<Point<N, 2>> {
+        self.original_range(p).map(|(start, _)| start)
+    }
+
+    /// The opposite corners, inclusive, of the original rectangle covered by `p`.
+    pub fn original_range(&self, p: Position) -> Option<(Point<N, 2>, Point<N, 2>)> {
+        let (x1, x2) = self.xs.range(p[0].try_into().ok()?)?;
+        let (y1, y2) = self.ys.range(p[1].try_into().ok()?)?;
+        Some((Point::new([x1, y1]), Point::new([x2, y2])))
+    }
+
+    /// How many original cells compressed cell `p` covers.
+    pub fn area(&self, p: Position) -> Option<N> {
+        let x = self.xs.span(p[0].try_into().ok()?)?;
+        let y = self.ys.span(p[1].try_into().ok()?)?;
+        Some(x * y)
+    }
+
+    /// The total original area of the cells in `grid` whose values satisfy `pred`.
+    pub fn area_where<V: Copy + Clone + Eq + PartialEq, P: Fn(V) -> bool>(
+        &self,
+        grid: &GridWorld<V>,
+        pred: P,
+    ) -> N {
+        grid.position_value_iter()
+            .filter(|(_, v)| pred(*v))
+            .map(|(p, _)| self.area(p).unwrap())
+            .fold(N::zero(), |total, a| total + a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{geometry::Polygon, multidim::Point};
+
+    use super::{CompressedAxis, CoordinateCompression};
+
+    #[test]
+    fn test_axis() {
+        let axis = CompressedAxis::new([1_000_000_000_u64, 7, 8, 20]);
+        assert_eq!(axis.len(), 6);
+        assert_eq!(axis.index_of(7), Some(0));
+        assert_eq!(axis.index_of(8), Some(1));
+        assert_eq!(axis.index_of(15), Some(2));
+        assert_eq!(axis.index_of(20), Some(3));
+        assert_eq!(axis.index_of(6), None);
+        assert_eq!(axis.index_of(1_000_000_001), None);
+        assert_eq!(axis.range(2), Some((9, 19)));
+        assert_eq!(axis.span(4), Some(1_000_000_000 - 21));
+        assert_eq!(axis.span(5), Some(1));
+        assert_eq!(axis.range(6), None);
+    }
+
+    #[test]
+    fn test_compressed_area() {
+        let vertices: Vec<Point<u64, 2>> = [
+            (7, 1),
+            (11, 1),
+            (11, 7),
+            (9, 7),
+            (9, 5),
+            (2, 5),
+            (2, 3),
+            (7, 3),
+        ]
+        .iter()
+        .map(|(x, y)| Point::new([*x, *y]))
+        .collect();
+        let compression = CoordinateCompression::new(&vertices);
+        assert_eq!((compression.width(), compression.height()), (7, 7));
+
+        let polygon = Polygon::new(vertices.clone());
+        let mut grid = compression.grid(false);
+        for p in grid.position_iter() {
+            let corner = compression.original(p).unwrap();
+            grid.update(p, polygon.contains(&corner, true));
+        }
+        assert_eq!(compression.area_where(&grid, |inside| inside), 46);
+
+        let p = compression.compress(Point::new([5, 4])).unwrap();
+        assert_eq!(
+            compression.original_range(p),
+            Some((Point::new([3, 4]), Point::new([6, 4])))
+        );
+        assert_eq!(compression.area(p), Some(4));
+    }
+}
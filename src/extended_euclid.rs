@@ -1,7 +1,8 @@
 use num::{Num, Signed};
 use std::{
     fmt::Display,
-    ops::{AddAssign, SubAssign},
+    iter::successors,
+    ops::{AddAssign, RangeInclusive, SubAssign},
 };
 use trait_set::trait_set;
 
@@ -9,70 +10,175 @@ trait_set! {
     pub trait EuclidNum = Num + Copy + Display + Signed + Ord + AddAssign + SubAssign;
 }
 
-#[derive(Debug)]
-pub struct LinearDiophantinePositive<N: EuclidNum> {
-    x: N,
-    y: N,
-    co_x: N,
-    co_y: N,
+/// Every integer solution of `ax + by = c`, which is
+/// `(x0 + t * step_x, y0 - t * step_y)` for each integer `t`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct LinearDiophantine<N: EuclidNum> {
+    x0: N,
+    y0: N,
+    step_x: N,
+    step_y: N,
 }
 
-impl<N: EuclidNum> LinearDiophantinePositive<N> {
-    pub fn new(a: N, b: N, c: N) -> Self {
+impl<N: EuclidNum> LinearDiophantine<N> {
+    /// Returns `None` if there are no integer solutions, or if `a` and `b` are both
+    /// zero, since then every pair solves `0 = c` when `c` is zero.
+    pub fn solve(a: N, b: N, c: N) -> Option<Self> {
         // From https://www.perplexity.ai/search/how-do-you-find-all-possible-x-B9.PeotlQLec9dYtLV_FlA
-        let (gcd, x, y) = gcd_x_y(a, b);
-        if c % gcd == N::zero() {
-            let goal_over_gcd = c / gcd;
-            let mut result = Self {
-                x: x * goal_over_gcd,
-                y: y * goal_over_gcd,
-                co_x: -(b / gcd),
-                co_y: a / gcd,
-            };
-            result.ensure_positive_y();
-            result.ensure_positive_x();
-            if x < y {
-                result.co_x = -result.co_x;
-                result.co_y = -result.co_y;
-            }
-            result
-        } else {
-            let neg = -N::one();
-            Self {
-                x: neg,
-                y: neg,
-                co_x: neg,
-                co_y: neg,
-            }
+        let (gcd, x, y) = extended_gcd(a, b);
+        if gcd == N::zero() || c % gcd != N::zero() {
+            return None;
         }
+        let goal_over_gcd = c / gcd;
+        Some(Self {
+            x0: x * goal_over_gcd,
+            y0: y * goal_over_gcd,
+            step_x: b / gcd,
+            step_y: a / gcd,
+        })
     }
 
-    fn ensure_positive_y(&mut self) {
-        if self.y < N::zero() {
-            let gap = -(self.y / self.co_y);
-            self.x += gap * self.co_x;
-            self.y += gap * self.co_y;
-            if self.y < num::zero() {
-                self.x += self.co_x;
-                self.y += self.co_y;
-            }
+    /// One particular solution `(x0, y0)`.
+    pub fn particular(&self) -> (N, N) {
+        (self.x0, self.y0)
+    }
+
+    /// How much `x` grows and `y` shrinks from one solution to the next.
+    pub fn step(&self) -> (N, N) {
+        (self.step_x, self.step_y)
+    }
+
+    /// The solution for parameter `t`.
+    pub fn solution(&self, t: N) -> (N, N) {
+        (self.x0 + t * self.step_x, self.y0 - t * self.step_y)
+    }
+
+    /// The range of `t`, inclusive, for which `x >= 0` and `y >= 0`. A `None` end is
+    /// unbounded; an empty range has its start above its end.
+    pub fn nonnegative_t(&self) -> (Option<N>, Option<N>) {
+        let mut t_range = (None, None);
+        let feasible = tighten(&mut t_range, self.x0, self.step_x, N::zero())
+            && tighten(&mut t_range, self.y0, -self.step_y, N::zero());
+        if feasible {
+            t_range
+        } else {
+            (Some(N::one()), Some(N::zero()))
         }
     }
 
-    fn ensure_positive_x(&mut self) {
-        if self.x < N::zero() {
-            let gap = self.x / self.co_x;
-            self.x -= gap * self.co_x;
-            self.y -= gap * self.co_y;
-            if self.x < N::zero() {
-                self.x -= self.co_x;
-                self.y -= self.co_y;
-            }
+    /// The range of `t`, inclusive, whose solutions lie within `xs` and `ys`, or
+    /// `None` if no solution does.
+    pub fn t_within(&self, xs: &RangeInclusive<N>, ys: &RangeInclusive<N>) -> Option<(N, N)> {
+        let mut t_range = (None, None);
+        let feasible = tighten(&mut t_range, self.x0, self.step_x, *xs.start())
+            && tighten(&mut t_range, -self.x0, -self.step_x, -*xs.end())
+            && tighten(&mut t_range, self.y0, -self.step_y, *ys.start())
+            && tighten(&mut t_range, -self.y0, self.step_y, -*ys.end());
+        match t_range {
+            (Some(lo), Some(hi)) if feasible && lo <= hi => Some((lo, hi)),
+            _ => None,
         }
     }
 
+    /// How many solutions lie within `xs` and `ys`.
+    pub fn count_within(&self, xs: &RangeInclusive<N>, ys: &RangeInclusive<N>) -> N {
+        self.t_within(xs, ys)
+            .map_or(N::zero(), |(lo, hi)| hi - lo + N::one())
+    }
+
+    /// The solutions within `xs` and `ys`, in increasing order of `t`.
+    pub fn solutions_within(
+        &self,
+        xs: &RangeInclusive<N>,
+        ys: &RangeInclusive<N>,
+    ) -> impl Iterator<Item = (N, N)> + '_ {
+        let t_range = self.t_within(xs, ys);
+        successors(t_range.map(|(lo, _)| lo), move |t| {
+            t_range.and_then(|(_, hi)| (*t < hi).then(|| *t + N::one()))
+        })
+        .map(|t| self.solution(t))
+    }
+
+    /// The solution within `xs` and `ys` minimizing `x_cost * x + y_cost * y`, along
+    /// with that cost. Since the cost is linear in `t`, it is smallest at one end of
+    /// the range of `t`; ties go to the smaller `t`.
+    pub fn minimize_within(
+        &self,
+        x_cost: N,
+        y_cost: N,
+        xs: &RangeInclusive<N>,
+        ys: &RangeInclusive<N>,
+    ) -> Option<((N, N), N)> {
+        let (lo, hi) = self.t_within(xs, ys)?;
+        let slope = x_cost * self.step_x - y_cost * self.step_y;
+        let (x, y) = self.solution(if slope < N::zero() { hi } else { lo });
+        Some(((x, y), x_cost * x + y_cost * y))
+    }
+}
+
+/// Narrows `t_range` to the `t` satisfying `base + t * slope >= bound`. Returns false
+/// if no `t` can.
+fn tighten<N: EuclidNum>(
+    t_range: &mut (Option<N>, Option<N>),
+    base: N,
+    slope: N,
+    bound: N,
+) -> bool {
+    if slope > N::zero() {
+        let t = div_ceil(bound - base, slope);
+        t_range.0 = Some(t_range.0.map_or(t, |lo| lo.max(t)));
+    } else if slope < N::zero() {
+        let t = div_floor(bound - base, slope);
+        t_range.1 = Some(t_range.1.map_or(t, |hi| hi.min(t)));
+    } else if base < bound {
+        return false;
+    }
+    true
+}
+
+/// The solutions of `ax + by = c` with `x` and `y` both non-negative, in increasing
+/// order of `x`, or of `y` when `b` is zero and `x` never changes.
+#[derive(Debug)]
+pub struct LinearDiophantinePositive<N: EuclidNum> {
+    solutions: LinearDiophantine<N>,
+    t: N,
+    t_step: N,
+    last_t: Option<N>,
+}
+
+impl<N: EuclidNum> LinearDiophantinePositive<N> {
+    /// Returns `None` when there are no integer solutions at all, or when the
+    /// non-negative solutions have no smallest `x` to start from.
+    pub fn new(a: N, b: N, c: N) -> Option<Self> {
+        let solutions = LinearDiophantine::solve(a, b, c)?;
+        let (lo, hi) = solutions.nonnegative_t();
+        // With `x` fixed, start from whichever end of `t` is bounded.
+        let ascending = if solutions.step_x == N::zero() {
+            lo.is_some()
+        } else {
+            solutions.step_x > N::zero()
+        };
+        let (t, t_step, last_t) = if ascending {
+            (lo?, N::one(), hi)
+        } else {
+            (hi?, -N::one(), lo)
+        };
+        Some(Self {
+            solutions,
+            t,
+            t_step,
+            last_t,
+        })
+    }
+
     pub fn live(&self) -> bool {
-        self.x >= N::zero() && self.y >= N::zero()
+        self.last_t.is_none_or(|last| {
+            if self.t_step > N::zero() {
+                self.t <= last
+            } else {
+                self.t >= last
+            }
+        })
     }
 }
 
@@ -81,9 +187,8 @@ impl<N: EuclidNum> Iterator for LinearDiophantinePositive<N> {
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.live() {
-            let result = (self.x, self.y);
-            self.x += self.co_x;
-            self.y += self.co_y;
+            let result = self.solutions.solution(self.t);
+            self.t += self.t_step;
             Some(result)
         } else {
             None
@@ -91,8 +196,10 @@ impl<N: EuclidNum> Iterator for LinearDiophantinePositive<N> {
     }
 }
 
+/// Returns `(g, x, y)` where `g` is the non-negative greatest common divisor of `a`
+/// and `b`, and `ax + by = g`.
 // From https://brilliant.org/wiki/extended-euclidean-algorithm/#extended-euclidean-algorithm
-fn gcd_x_y<N: EuclidNum>(a: N, b: N) -> (N, N, N) {
+pub fn extended_gcd<N: EuclidNum>(a: N, b: N) -> (N, N, N) {
     let mut s = N::zero();
     let mut old_s = N::one();
     let mut t = N::one();
@@ -107,5 +214,170 @@ fn gcd_x_y<N: EuclidNum>(a: N, b: N) -> (N, N, N) {
         (old_t, t) = (t, old_t - quotient * t);
     }
 
-    (old_r, old_s, old_t)
+    if old_r < N::zero() {
+        (-old_r, -old_s, -old_t)
+    } else {
+        (old_r, old_s, old_t)
+    }
+}
+
+/// The `x` in `0..m` with `ax ≡ 1 (mod m)`, if `a` and `m` are coprime and `m` is
+/// nonzero.
+pub fn mod_inverse<N: EuclidNum>(a: N, m: N) -> Option<N> {
+    if m == N::zero() {
+        return None;
+    }
+    let (gcd, x, _) = extended_gcd(a, m);
+    (gcd == N::one()).then(|| mod_floor(x, m.abs()))
+}
+
+/// Solves the system `x ≡ residue (mod modulus)` for each pair, whose moduli need not
+/// be coprime. Returns the smallest non-negative solution and the least common
+/// multiple of the moduli, or `None` if the congruences conflict or a modulus is zero.
+pub fn chinese_remainder<N: EuclidNum>(congruences: &[(N, N)]) -> Option<(N, N)> {
+    let mut x = N::zero();
+    let mut modulus = N::one();
+    for (residue, m) in congruences.iter() {
+        if *m == N::zero() {
+            return None;
+        }
+        let m = m.abs();
+        let (gcd, p, _) = extended_gcd(modulus, m);
+        let gap = *residue - x;
+        if gap % gcd != N::zero() {
+            return None;
+        }
+        let m_over_gcd = m / gcd;
+        let k = mod_floor((gap / gcd) * p, m_over_gcd);
+        let lcm = modulus * m_over_gcd;
+        x = mod_floor(x + modulus * k, lcm);
+        modulus = lcm;
+    }
+    Some((x, modulus))
+}
+
+fn mod_floor<N: EuclidNum>(a: N, m: N) -> N {
+    let r = a % m;
+    if r < N::zero() { r + m } else { r }
+}
+
+fn div_floor<N: EuclidNum>(a: N, b: N) -> N {
+    let q = a / b;
+    if a % b != N::zero() && (a < N::zero()) != (b < N::zero()) {
+        q - N::one()
+    } else {
+        q
+    }
+}
+
+fn div_ceil<N: EuclidNum>(a: N, b: N) -> N {
+    -div_floor(-a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::{
+        LinearDiophantine, LinearDiophantinePositive, chinese_remainder, extended_gcd, mod_inverse,
+    };
+
+    #[test]
+    fn test_extended_gcd() {
+        for (a, b) in [(240, 46), (-240, 46), (17, 0), (0, -5), (12, 18)] {
+            let (g, x, y) = extended_gcd::<i64>(a, b);
+            assert!(g >= 0);
+            assert_eq!(a * x + b * y, g);
+            assert_eq!(a % g.max(1), 0);
+            assert_eq!(b % g.max(1), 0);
+        }
+        assert_eq!(extended_gcd(240, 46).0, 2);
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(-3, 11), Some(7));
+        assert_eq!(mod_inverse(6, 9), None);
+        assert_eq!(mod_inverse(1, 0), None);
+    }
+
+    #[test]
+    fn test_general_solution() {
+        let solutions = LinearDiophantine::solve(6, 9, 21).unwrap();
+        for t in -5..5 {
+            let (x, y) = solutions.solution(t);
+            assert_eq!(6 * x + 9 * y, 21);
+        }
+        assert_eq!(solutions.step(), (3, 2));
+        assert_eq!(LinearDiophantine::solve(6, 9, 20), None);
+        assert_eq!(LinearDiophantine::solve(0, 0, 0), None);
+    }
+
+    #[test]
+    fn test_positive() {
+        let found = LinearDiophantinePositive::new(3, 5, 47)
+            .unwrap()
+            .collect_vec();
+        assert_eq!(found, vec![(4, 7), (9, 4), (14, 1)]);
+        assert!(
+            LinearDiophantinePositive::new(3, 5, 4)
+                .unwrap()
+                .next()
+                .is_none()
+        );
+        assert!(LinearDiophantinePositive::new(4, 6, 5).is_none());
+        let unbounded = LinearDiophantinePositive::new(3, -5, 1).unwrap();
+        assert_eq!(
+            unbounded.take(3).collect_vec(),
+            vec![(2, 1), (7, 4), (12, 7)]
+        );
+        let fixed_x = LinearDiophantinePositive::new(3, 0, 6).unwrap();
+        assert_eq!(fixed_x.take(3).collect_vec(), vec![(2, 0), (2, 1), (2, 2)]);
+        let fixed_y = LinearDiophantinePositive::new(0, 3, 6).unwrap();
+        assert_eq!(fixed_y.take(3).collect_vec(), vec![(0, 2), (1, 2), (2, 2)]);
+    }
+
+    #[test]
+    fn test_chinese_remainder() {
+        assert_eq!(
+            chinese_remainder(&[(2, 3), (3, 5), (2, 7)]),
+            Some((23, 105))
+        );
+        assert_eq!(chinese_remainder(&[(3, 4), (5, 6)]), Some((11, 12)));
+        assert_eq!(chinese_remainder(&[(1, 4), (2, 6)]), None);
+        assert_eq!(chinese_remainder(&[(1, 0)]), None);
+        // Bus schedule: buses 7, 13, 59, 31, 19 at offsets 0, 1, 4, 6, 7.
+        let buses = [(7, 0), (13, 1), (59, 4), (31, 6), (19, 7)]
+            .iter()
+            .map(|(bus, offset): &(i64, i64)| (-offset, *bus))
+            .collect_vec();
+        assert_eq!(
+            chinese_remainder(&buses),
+            Some((1068781, 7 * 13 * 59 * 31 * 19))
+        );
+    }
+
+    #[test]
+    fn test_within_bounds() {
+        let solutions = LinearDiophantine::solve(94, 22, 8400).unwrap();
+        let (xs, ys) = (0..=100, 0..=100);
+        assert_eq!(solutions.count_within(&xs, &ys), 2);
+        let mut found = solutions.solutions_within(&xs, &ys).collect_vec();
+        found.sort();
+        assert_eq!(found, vec![(69, 87), (80, 40)]);
+        assert_eq!(
+            solutions.minimize_within(3, 1, &xs, &ys),
+            Some(((80, 40), 280))
+        );
+        assert_eq!(
+            solutions.minimize_within(1, 3, &xs, &ys),
+            Some(((80, 40), 200))
+        );
+        assert_eq!(solutions.count_within(&(0..=60), &ys), 0);
+        assert_eq!(solutions.minimize_within(3, 1, &(0..=60), &ys), None);
+
+        let dense = LinearDiophantine::solve(1, 1, 10).unwrap();
+        assert_eq!(dense.count_within(&(-5..=20), &(0..=10)), 11);
+        assert_eq!(
+            dense.minimize_within(2, -1, &(-5..=20), &(0..=10)),
+            Some(((0, 10), -10))
+        );
+    }
 }
@@ -0,0 +1,481 @@
+use anyhow::bail;
+use bare_metal_modulo::NumType;
+use num::Integer;
+
+use crate::{
+    compress::CoordinateCompression,
+    multidim::{Point, Position},
+};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Containment {
+    Inside,
+    Boundary,
+    Outside,
+}
+
+/// A polygon with lattice-point vertices. The last vertex connects back to the first.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Polygon<N: NumType> {
+    vertices: Vec<Point<N, 2>>,
+}
+
+impl<N: NumType> Polygon<N> {
+    pub fn new(vertices: Vec<Point<N, 2>>) -> Self {
+        Self { vertices }
+    }
+
+    /// Builds a polygon from a path that must end where it began.
+    pub fn from_closed_path(mut path: Vec<Point<N, 2>>) -> anyhow::Result<Self> {
+        if path.len() < 2 || path[0] != path[path.len() - 1] {
+            bail!("Path does not return to its starting point");
+        }
+        path.pop();
+        Ok(Self::new(path))
+    }
+
+    pub fn vertices(&self) -> &Vec<Point<N, 2>> {
+        &self.vertices
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = (Point<N, 2>, Point<N, 2>)> + '_ {
+        (0..self.vertices.len()).map(|i| {
+            (
+                self.vertices[i],
+                self.vertices[(i + 1) % self.vertices.len()],
+            )
+        })
+    }
+
+    /// Twice the signed shoelace area: positive for counterclockwise vertices in a
+    /// y-up coordinate system.
+    pub fn double_signed_area(&self) -> i128 {
+        self.edges()
+            .map(|(a, b)| wide(a[0]) * wide(b[1]) - wide(b[0]) * wide(a[1]))
+            .sum()
+    }
+
+    pub fn double_area(&self) -> u128 {
+        self.double_signed_area().unsigned_abs()
+    }
+
+    pub fn area(&self) -> f64 {
+        self.double_area() as f64 / 2.0
+    }
+
+    /// Number of lattice points on the edges.
+    pub fn boundary_points(&self) -> u128 {
+        self.edges()
+            .map(|(a, b)| {
+                let dx = (wide(b[0]) - wide(a[0])).unsigned_abs();
+                let dy = (wide(b[1]) - wide(a[1])).unsigned_abs();
+                dx.gcd(&dy)
+            })
+            .sum()
+    }
+
+    /// Number of lattice points strictly inside, by Pick's theorem. Degenerate
+    /// polygons that enclose no area have none.
+    pub fn interior_points(&self) -> u128 {
+        (self.double_area() + 2).saturating_sub(self.boundary_points()) / 2
+    }
+
+    /// Number of lattice points inside or on the boundary. For a polygon traced
+    /// through the centers of grid cells, this counts the enclosed cells.
+    pub fn enclosed_points(&self) -> u128 {
+        self.interior_points() + self.boundary_points()
+    }
+
+    pub fn containment(&self, p: &Point<N, 2>) -> Containment {
+        if self.edges().any(|(a, b)| on_segment(p, &a, &b)) {
+            return Containment::Boundary;
+        }
+        let (x, y) = (wide(p[0]), wide(p[1]));
+        let mut inside = false;
+        for (a, b) in self.edges() {
+            let (ax, ay, bx, by) = (wide(a[0]), wide(a[1]), wide(b[0]), wide(b[1]));
+            if (ay > y) != (by > y) {
+                // Crossing x is ax + (y - ay) * (bx - ax) / (by - ay); compare without dividing.
+                let lhs = (x - ax) * (by - ay);
+                let rhs = (y - ay) * (bx - ax);
+                if (by > ay && lhs < rhs) || (by < ay && lhs > rhs) {
+                    inside = !inside;
+                }
+            }
+        }
+        if inside {
+            Containment::Inside
+        } else {
+            Containment::Outside
+        }
+    }
+
+    pub fn contains(&self, p: &Point<N, 2>, include_boundary: bool) -> bool {
+        match self.containment(p) {
+            Containment::Inside => true,
+            Containment::Boundary => include_boundary,
+            Containment::Outside => false,
+        }
+    }
+
+    pub fn is_rectilinear(&self) -> bool {
+        self.edges().all(|(a, b)| a[0] == b[0] || a[1] == b[1])
+    }
+
+    /// True when no two edges touch except consecutive edges at their shared vertex.
+    pub fn is_simple(&self) -> bool {
+        let edges = self.edges().collect::<Vec<_>>();
+        let n = edges.len();
+        for i in 0..n {
+            for j in i + 1..n {
+                let (a, b) = edges[i];
+                let (c, d) = edges[j];
+                if j == i + 1 || (i == 0 && j == n - 1) {
+                    // Consecutive edges share a vertex, so only overlap beyond it counts.
+                    let (far1, far2) = if j == i + 1 { (a, d) } else { (b, c) };
+                    if on_segment(&far1, &c, &d) || on_segment(&far2, &a, &b) {
+                        return false;
+                    }
+                } else if segments_intersect(&a, &b, &c, &d) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.vertices.len() < 3 {
+            bail!("A polygon needs at least 3 vertices");
+        }
+        if self.edges().any(|(a, b)| a == b) {
+            bail!("Polygon has a zero-length edge");
+        }
+        if !self.is_simple() {
+            bail!("Polygon intersects itself");
+        }
+        Ok(())
+    }
+
+    /// Like `validate`, but also requires every edge to be horizontal or vertical.
+    pub fn validate_rectilinear(&self) -> anyhow::Result<()> {
+        self.validate()?;
+        if let Some((a, b)) = self.edges().find(|(a, b)| a[0] != b[0] && a[1] != b[1]) {
+            bail!("Edge from {a} to {b} is not horizontal or vertical");
+        }
+        Ok(())
+    }
+}
+
+/// Preprocesses a rectilinear polygon so that whether an axis-aligned rectangle lies
+/// within it (boundary included) can be answered in constant time. Coordinate
+/// compression groups tiles into blocks that share the same containment, and a 2D
+/// prefix sum counts the blocks lying outside the polygon.
+pub struct RectilinearRegion {
+    compression: CoordinateCompression<u64>,
+    outside_sums: Vec<Vec<usize>>,
+}
+
+impl RectilinearRegion {
+    pub fn new(vertices: &[Point<u64, 2>]) -> Self {
+        let polygon = Polygon::new(vertices.to_vec());
+        let compression = CoordinateCompression::new(vertices);
+        let (width, height) = (compression.width(), compression.height());
+        let mut boundary = compression.grid(false);
+        for (a, b) in polygon.edges() {
+            let (a, b) = (
+                compression.compress(a).unwrap(),
+                compression.compress(b).unwrap(),
+            );
+            for x in a[0].min(b[0])..=a[0].max(b[0]) {
+                for y in a[1].min(b[1])..=a[1].max(b[1]) {
+                    boundary.update(Position::new([x, y]), true);
+                }
+            }
+        }
+
+        // Every tile in a block shares its containment, so each block off the boundary
+        // is classified by the parity of the vertical edges to the right of its corner.
+        // A flood fill would miss outside pockets reachable only between edges on
+        // adjacent coordinates.
+        let mut outside = vec![vec![false; height]; width];
+        for y in 0..height {
+            let row = compression.ys().range(y).unwrap().0;
+            let mut crossings = polygon
+                .edges()
+                .filter(|(a, b)| (a[1] > row) != (b[1] > row))
+                .map(|(a, _)| a[0])
+                .collect::<Vec<_>>();
+            crossings.sort();
+            for (x, column) in outside.iter_mut().enumerate() {
+                if boundary.value(Position::from_usize(x, y)) == Some(false) {
+                    let corner = compression.xs().range(x).unwrap().0;
+                    let right = crossings.len() - crossings.partition_point(|c| *c <= corner);
+                    column[y] = right % 2 == 0;
+                }
+            }
+        }
+
+        let mut outside_sums = vec![vec![0; height + 1]; width + 1];
+        for x in 0..width {
+            for y in 0..height {
+                let here = outside[x][y] as usize;
+                outside_sums[x + 1][y + 1] =
+                    here + outside_sums[x][y + 1] + outside_sums[x + 1][y] - outside_sums[x][y];
+            }
+        }
+        Self {
+            compression,
+            outside_sums,
+        }
+    }
+
+    pub fn contains(&self, p: &Point<u64, 2>) -> bool {
+        self.contains_rectangle(p, p)
+    }
+
+    /// Whether every tile of the rectangle with opposite corners `a` and `b` is inside
+    /// the polygon or on its boundary.
+    pub fn contains_rectangle(&self, a: &Point<u64, 2>, b: &Point<u64, 2>) -> bool {
+        let (Some(a), Some(b)) = (self.compression.compress(*a), self.compression.compress(*b))
+        else {
+            return false;
+        };
+        let (x1, x2) = (a[0].min(b[0]) as usize, a[0].max(b[0]) as usize + 1);
+        let (y1, y2) = (a[1].min(b[1]) as usize, a[1].max(b[1]) as usize + 1);
+        let s = &self.outside_sums;
+        s[x2][y2] + s[x1][y1] == s[x1][y2] + s[x2][y1]
+    }
+}
+
+/// Finds the rectangle with the most tiles whose opposite corners are both vertices
+/// and which lies entirely within the polygon. Returns its corners and tile count.
+pub fn largest_rectangle_within(
+    vertices: &[Point<u64, 2>],
+) -> Option<(Point<u64, 2>, Point<u64, 2>, u64)> {
+    let region = RectilinearRegion::new(vertices);
+    let mut best = None;
+    for i in 0..vertices.len() {
+        for j in i + 1..vertices.len() {
+            let (a, b) = (vertices[i], vertices[j]);
+            let area = (a[0].abs_diff(b[0]) + 1) * (a[1].abs_diff(b[1]) + 1);
+            if best.is_none_or(|(_, _, best_area)| area > best_area)
+                && region.contains_rectangle(&a, &b)
+            {
+                best = Some((a, b, area));
+            }
+        }
+    }
+    best
+}
+
+fn wide<N: NumType>(n: N) -> i128 {
+    n.to_i128().unwrap()
+}
+
+fn cross<N: NumType>(o: &Point<N, 2>, a: &Point<N, 2>, b: &Point<N, 2>) -> i128 {
+    (wide(a[0]) - wide(o[0])) * (wide(b[1]) - wide(o[1]))
+        - (wide(a[1]) - wide(o[1])) * (wide(b[0]) - wide(o[0]))
+}
+
+fn on_segment<N: NumType>(p: &Point<N, 2>, a: &Point<N, 2>, b: &Point<N, 2>) -> bool {
+    cross(a, b, p) == 0 && (0..2).all(|i| a[i].min(b[i]) <= p[i] && p[i] <= a[i].max(b[i]))
+}
+
+fn segments_intersect<N: NumType>(
+    a: &Point<N, 2>,
+    b: &Point<N, 2>,
+    c: &Point<N, 2>,
+    d: &Point<N, 2>,
+) -> bool {
+    let d1 = cross(c, d, a).signum();
+    let d2 = cross(c, d, b).signum();
+    let d3 = cross(a, b, c).signum();
+    let d4 = cross(a, b, d).signum();
+    (d1 * d2 < 0 && d3 * d4 < 0)
+        || on_segment(a, c, d)
+        || on_segment(b, c, d)
+        || on_segment(c, a, b)
+        || on_segment(d, a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::multidim::Point;
+
+    use super::{Containment, Polygon, RectilinearRegion, largest_rectangle_within};
+
+    fn polygon(points: &[(u64, u64)]) -> Polygon<u64> {
+        Polygon::new(points.iter().map(|(x, y)| Point::new([*x, *y])).collect())
+    }
+
+    const RED_TILES: [(u64, u64); 8] = [
+        (7, 1),
+        (11, 1),
+        (11, 7),
+        (9, 7),
+        (9, 5),
+        (2, 5),
+        (2, 3),
+        (7, 3),
+    ];
+
+    #[test]
+    fn test_area() {
+        let square = polygon(&[(0, 0), (4, 0), (4, 4), (0, 4)]);
+        assert_eq!(square.area(), 16.0);
+        assert_eq!(square.boundary_points(), 16);
+        assert_eq!(square.interior_points(), 9);
+        assert_eq!(square.enclosed_points(), 25);
+
+        let triangle = polygon(&[(0, 0), (3, 0), (0, 3)]);
+        assert_eq!(triangle.double_area(), 9);
+        assert_eq!(triangle.area(), 4.5);
+        assert_eq!(triangle.boundary_points(), 9);
+        assert_eq!(triangle.interior_points(), 1);
+
+        let tiles = polygon(&RED_TILES);
+        assert_eq!(tiles.area(), 30.0);
+        assert_eq!(tiles.enclosed_points(), 46);
+
+        let segment = polygon(&[(0, 0), (5, 0)]);
+        assert_eq!(segment.interior_points(), 0);
+    }
+
+    #[test]
+    fn test_containment() {
+        let tiles = polygon(&RED_TILES);
+        for ((x, y), expected) in [
+            ((8, 2), Containment::Inside),
+            ((3, 4), Containment::Inside),
+            ((10, 6), Containment::Inside),
+            ((7, 1), Containment::Boundary),
+            ((11, 4), Containment::Boundary),
+            ((5, 5), Containment::Boundary),
+            ((3, 2), Containment::Outside),
+            ((8, 6), Containment::Outside),
+            ((0, 0), Containment::Outside),
+            ((12, 3), Containment::Outside),
+        ] {
+            let p = Point::new([x, y]);
+            assert_eq!(tiles.containment(&p), expected, "{p}");
+        }
+        assert!(tiles.contains(&Point::new([7, 1]), true));
+        assert!(!tiles.contains(&Point::new([7, 1]), false));
+    }
+
+    #[test]
+    fn test_validation() {
+        let tiles = polygon(&RED_TILES);
+        assert!(tiles.is_rectilinear());
+        assert!(tiles.is_simple());
+        assert!(tiles.validate().is_ok());
+        assert!(tiles.validate_rectilinear().is_ok());
+        assert!(polygon(&[(0, 0), (3, 0), (0, 3)]).validate().is_ok());
+        assert!(
+            polygon(&[(0, 0), (3, 0), (0, 3)])
+                .validate_rectilinear()
+                .is_err()
+        );
+
+        assert!(!polygon(&[(0, 0), (3, 0), (0, 3)]).is_rectilinear());
+        let bowtie = polygon(&[(0, 0), (2, 2), (2, 0), (0, 2)]);
+        assert!(!bowtie.is_simple());
+        assert!(bowtie.validate().is_err());
+        let backtrack = polygon(&[(0, 0), (4, 0), (2, 0), (2, 2)]);
+        assert!(!backtrack.is_simple());
+        assert!(polygon(&[(0, 0), (1, 1)]).validate().is_err());
+        assert!(
+            polygon(&[(0, 0), (0, 0), (1, 1), (1, 0)])
+                .validate()
+                .is_err()
+        );
+
+        let closed = vec![
+            Point::new([0, 0]),
+            Point::new([2, 0]),
+            Point::new([2, 2]),
+            Point::new([0, 0]),
+        ];
+        assert_eq!(
+            Polygon::<u64>::from_closed_path(closed)
+                .unwrap()
+                .vertices()
+                .len(),
+            3
+        );
+        let open = vec![Point::new([0, 0]), Point::new([2, 0]), Point::new([2, 2])];
+        assert!(Polygon::<u64>::from_closed_path(open).is_err());
+    }
+
+    #[test]
+    fn test_rectilinear_region() {
+        let vertices: Vec<_> = RED_TILES
+            .iter()
+            .map(|(x, y)| Point::new([*x, *y]))
+            .collect();
+        let region = RectilinearRegion::new(&vertices);
+        let tiles = polygon(&RED_TILES);
+        for x in 0..=13 {
+            for y in 0..=9 {
+                let p = Point::new([x, y]);
+                assert_eq!(region.contains(&p), tiles.contains(&p, true), "{p}");
+            }
+        }
+        assert!(region.contains_rectangle(&Point::new([9, 5]), &Point::new([2, 3])));
+        assert!(!region.contains_rectangle(&Point::new([7, 1]), &Point::new([2, 5])));
+
+        let (a, b, area) = largest_rectangle_within(&vertices).unwrap();
+        assert_eq!(area, 24);
+        assert!(region.contains_rectangle(&a, &b));
+    }
+
+    #[test]
+    fn test_sealed_pocket() {
+        // The pocket around (5, 5) opens to the outside only through the slit between
+        // the edges at y = 5 and y = 6, which holds no tiles.
+        let points = [
+            (0, 0),
+            (10, 0),
+            (10, 10),
+            (0, 10),
+            (0, 6),
+            (3, 6),
+            (3, 8),
+            (7, 8),
+            (7, 2),
+            (3, 2),
+            (3, 5),
+            (0, 5),
+        ];
+        let tiles = polygon(&points);
+        assert!(tiles.validate_rectilinear().is_ok());
+        let region = RectilinearRegion::new(tiles.vertices());
+        for x in 0..=11 {
+            for y in 0..=11 {
+                let p = Point::new([x, y]);
+                assert_eq!(region.contains(&p), tiles.contains(&p, true), "{p}");
+            }
+        }
+        assert!(!region.contains(&Point::new([5, 5])));
+
+        let (_, _, area) = largest_rectangle_within(tiles.vertices()).unwrap();
+        let brute = tiles
+            .vertices()
+            .iter()
+            .tuple_combinations()
+            .filter(|(a, b)| {
+                (a[0].min(b[0])..=a[0].max(b[0])).all(|x| {
+                    (a[1].min(b[1])..=a[1].max(b[1]))
+                        .all(|y| tiles.contains(&Point::new([x, y]), true))
+                })
+            })
+            .map(|(a, b)| (a[0].abs_diff(b[0]) + 1) * (a[1].abs_diff(b[1]) + 1))
+            .max()
+            .unwrap();
+        assert_eq!(area, brute);
+        assert!(area < 121);
+    }
+}
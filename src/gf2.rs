@@ -0,0 +1,401 @@
+use std::ops::{BitXor, BitXorAssign};
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// The most free variables `Gf2SolutionSpace::min_weight_solution` will search.
+pub const MAX_SEARCH_FREE_VARS: usize = 25;
+
+/// A fixed-length vector over GF(2), packed into words.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Gf2Vector {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl Gf2Vector {
+    /// The all-zero vector of length `len`.
+    pub fn new(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(WORD_BITS)],
+            len,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, i: usize) -> bool {
+        assert!(i < self.len);
+        self.words[i / WORD_BITS] >> (i % WORD_BITS) & 1 == 1
+    }
+
+    pub fn set(&mut self, i: usize, value: bool) {
+        assert!(i < self.len);
+        let mask = 1 << (i % WORD_BITS);
+        if value {
+            self.words[i / WORD_BITS] |= mask;
+        } else {
+            self.words[i / WORD_BITS] &= !mask;
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    /// The number of ones, also known as the Hamming weight.
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Indices of the ones, in increasing order.
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len).filter(|i| self.get(*i))
+    }
+
+    /// The dot product over GF(2).
+    pub fn dot(&self, other: &Self) -> bool {
+        assert_eq!(self.len, other.len);
+        self.words
+            .iter()
+            .zip(other.words.iter())
+            .map(|(a, b)| (a & b).count_ones())
+            .sum::<u32>()
+            % 2
+            == 1
+    }
+}
+
+impl FromIterator<bool> for Gf2Vector {
+    fn from_iter<T: IntoIterator<Item = bool>>(iter: T) -> Self {
+        let bits = iter.into_iter().collect::<Vec<_>>();
+        let mut result = Self::new(bits.len());
+        for (i, bit) in bits.into_iter().enumerate() {
+            result.set(i, bit);
+        }
+        result
+    }
+}
+
+impl BitXorAssign<&Gf2Vector> for Gf2Vector {
+    fn bitxor_assign(&mut self, rhs: &Gf2Vector) {
+        assert_eq!(self.len, rhs.len);
+        for (w, r) in self.words.iter_mut().zip(rhs.words.iter()) {
+            *w ^= r;
+        }
+    }
+}
+
+impl BitXor<&Gf2Vector> for Gf2Vector {
+    type Output = Self;
+
+    fn bitxor(mut self, rhs: &Gf2Vector) -> Self::Output {
+        self ^= rhs;
+        self
+    }
+}
+
+/// A matrix over GF(2), stored as packed rows.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Gf2Matrix {
+    rows: Vec<Gf2Vector>,
+    num_cols: usize,
+}
+
+impl Gf2Matrix {
+    /// Each row is a packed bit vector of length `num_cols`.
+    pub fn new(rows: Vec<Gf2Vector>, num_cols: usize) -> Self {
+        assert!(rows.iter().all(|row| row.len() == num_cols));
+        Self { rows, num_cols }
+    }
+
+    /// The matrix whose columns are `columns`, each of length `num_rows`. Convenient
+    /// for toggling puzzles, where each button is a column.
+    pub fn from_columns(columns: &[Gf2Vector], num_rows: usize) -> Self {
+        assert!(columns.iter().all(|col| col.len() == num_rows));
+        let rows = (0..num_rows)
+            .map(|r| columns.iter().map(|col| col.get(r)).collect())
+            .collect();
+        Self::new(rows, columns.len())
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn num_cols(&self) -> usize {
+        self.num_cols
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> bool {
+        self.rows[row].get(col)
+    }
+
+    /// The product `self * x`.
+    pub fn mul_vector(&self, x: &Gf2Vector) -> Gf2Vector {
+        self.rows.iter().map(|row| row.dot(x)).collect()
+    }
+
+    /// Eliminates with row XORs, since over GF(2) every nonzero pivot is already 1.
+    /// Returns the reduced matrix and its pivot columns, in row order.
+    pub fn reduced_row_echelon(&self) -> (Self, Vec<usize>) {
+        let mut rows = self.rows.clone();
+        let mut pivots = vec![];
+        for col in 0..self.num_cols {
+            let row = pivots.len();
+            let Some(found) = (row..rows.len()).find(|r| rows[*r].get(col)) else {
+                continue;
+            };
+            rows.swap(row, found);
+            let pivot_row = rows[row].clone();
+            for (other, other_row) in rows.iter_mut().enumerate() {
+                if other != row && other_row.get(col) {
+                    *other_row ^= &pivot_row;
+                }
+            }
+            pivots.push(col);
+        }
+        (
+            Self {
+                rows,
+                num_cols: self.num_cols,
+            },
+            pivots,
+        )
+    }
+
+    pub fn rank(&self) -> usize {
+        self.reduced_row_echelon().1.len()
+    }
+
+    /// Finds the sets of columns whose XOR is `rhs`, such as the buttons that toggle
+    /// the lights into a target pattern. Returns `None` if no set of columns works.
+    pub fn solve(&self, rhs: &Gf2Vector) -> Option<Gf2SolutionSpace> {
+        assert_eq!(rhs.len(), self.num_rows());
+        let augmented = Self::new(
+            self.rows
+                .iter()
+                .enumerate()
+                .map(|(r, row)| {
+                    (0..self.num_cols)
+                        .map(|c| row.get(c))
+                        .chain([rhs.get(r)])
+                        .collect()
+                })
+                .collect(),
+            self.num_cols + 1,
+        );
+        let (reduced, pivots) = augmented.reduced_row_echelon();
+        if pivots.last() == Some(&self.num_cols) {
+            return None;
+        }
+
+        let free = (0..self.num_cols)
+            .filter(|c| !pivots.contains(c))
+            .collect::<Vec<_>>();
+        let mut particular = Gf2Vector::new(self.num_cols);
+        for (row, col) in pivots.iter().enumerate() {
+            particular.set(*col, reduced.rows[row].get(self.num_cols));
+        }
+        let null_space = free
+            .iter()
+            .map(|f| {
+                let mut basis = Gf2Vector::new(self.num_cols);
+                basis.set(*f, true);
+                for (row, col) in pivots.iter().enumerate() {
+                    basis.set(*col, reduced.rows[row].get(*f));
+                }
+                basis
+            })
+            .collect();
+        Some(Gf2SolutionSpace {
+            particular,
+            null_space,
+            pivots,
+            free,
+        })
+    }
+}
+
+/// Every solution of a consistent system over GF(2): the particular solution XORed
+/// with any subset of the null-space basis vectors.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Gf2SolutionSpace {
+    particular: Gf2Vector,
+    null_space: Vec<Gf2Vector>,
+    pivots: Vec<usize>,
+    free: Vec<usize>,
+}
+
+impl Gf2SolutionSpace {
+    /// The solution with every free variable set to zero.
+    pub fn particular(&self) -> &Gf2Vector {
+        &self.particular
+    }
+
+    /// One basis vector per free variable, with that variable set and the other free
+    /// variables clear.
+    pub fn null_space(&self) -> &Vec<Gf2Vector> {
+        &self.null_space
+    }
+
+    pub fn rank(&self) -> usize {
+        self.pivots.len()
+    }
+
+    /// Columns holding a pivot, whose values follow from the free variables.
+    pub fn pivot_vars(&self) -> &Vec<usize> {
+        &self.pivots
+    }
+
+    pub fn free_vars(&self) -> &Vec<usize> {
+        &self.free
+    }
+
+    pub fn is_unique(&self) -> bool {
+        self.free.is_empty()
+    }
+
+    /// The number of solutions, if it fits in a `u64`.
+    pub fn num_solutions(&self) -> Option<u64> {
+        1u64.checked_shl(self.free.len() as u32)
+    }
+
+    /// The solution whose free variables take the given values, in the order of
+    /// `free_vars()`.
+    pub fn solution(&self, free_values: &[bool]) -> Gf2Vector {
+        assert_eq!(free_values.len(), self.free.len());
+        let mut result = self.particular.clone();
+        for (basis, value) in self.null_space.iter().zip(free_values.iter()) {
+            if *value {
+                result ^= basis;
+            }
+        }
+        result
+    }
+
+    /// Every solution, in Gray code order so that each step costs a single XOR.
+    /// Returns `None` when there are too many to count with `num_solutions()`.
+    pub fn solutions(&self) -> Option<Gf2Solutions<'_>> {
+        Some(Gf2Solutions {
+            space: self,
+            current: self.particular.clone(),
+            step: 0,
+            total: self.num_solutions()?,
+        })
+    }
+
+    /// A solution with the fewest ones, found by trying every solution. That takes
+    /// `2^free` steps, so this returns `None` beyond `MAX_SEARCH_FREE_VARS` free
+    /// variables.
+    pub fn min_weight_solution(&self) -> Option<Gf2Vector> {
+        if self.free.len() > MAX_SEARCH_FREE_VARS {
+            return None;
+        }
+        self.solutions()?.min_by_key(|s| s.count_ones())
+    }
+}
+
+pub struct Gf2Solutions<'a> {
+    space: &'a Gf2SolutionSpace,
+    current: Gf2Vector,
+    step: u64,
+    total: u64,
+}
+
+impl Iterator for Gf2Solutions<'_> {
+    type Item = Gf2Vector;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.step == self.total {
+            return None;
+        }
+        if self.step > 0 {
+            let flip = self.step.trailing_zeros() as usize;
+            self.current ^= &self.space.null_space[flip];
+        }
+        self.step += 1;
+        Some(self.current.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::{Gf2Matrix, Gf2Vector};
+
+    fn bits(s: &str) -> Gf2Vector {
+        s.chars().map(|c| c == '1').collect()
+    }
+
+    #[test]
+    fn test_day10_lights() {
+        // [.##.] (3) (1,3) (2) (2,3) (0,2) (0,1)
+        let buttons = [
+            vec![3],
+            vec![1, 3],
+            vec![2],
+            vec![2, 3],
+            vec![0, 2],
+            vec![0, 1],
+        ]
+        .iter()
+        .map(|lights| (0..4).map(|i| lights.contains(&i)).collect())
+        .collect_vec();
+        let m = Gf2Matrix::from_columns(&buttons, 4);
+        assert_eq!(m.rank(), 4);
+        let target = bits("0110");
+        let space = m.solve(&target).unwrap();
+        assert_eq!(space.free_vars().len(), 2);
+        assert_eq!(space.num_solutions(), Some(4));
+        let all = space.solutions().unwrap().collect_vec();
+        assert_eq!(all.len(), 4);
+        assert_eq!(all.iter().unique().count(), 4);
+        assert!(all.iter().all(|x| m.mul_vector(x) == target));
+        assert_eq!(space.min_weight_solution().unwrap().count_ones(), 2);
+    }
+
+    #[test]
+    fn test_inconsistent_and_wide() {
+        let m = Gf2Matrix::new(vec![bits("11"), bits("11")], 2);
+        assert_eq!(m.rank(), 1);
+        assert!(m.solve(&bits("10")).is_none());
+
+        // A chain of 100 buttons, each toggling two adjacent lights.
+        let buttons = (0..100)
+            .map(|b| (0..101).map(|i| i == b || i == b + 1).collect())
+            .collect_vec();
+        let m = Gf2Matrix::from_columns(&buttons, 101);
+        let mut target = Gf2Vector::new(101);
+        target.set(0, true);
+        target.set(100, true);
+        let space = m.solve(&target).unwrap();
+        assert!(space.is_unique());
+        assert_eq!(space.particular().count_ones(), 100);
+        assert_eq!(m.mul_vector(space.particular()), target);
+
+        // 70 buttons that all toggle the same light leave 69 free variables.
+        let buttons = vec![Gf2Vector::from_iter([true]); 70];
+        let space = Gf2Matrix::from_columns(&buttons, 1)
+            .solve(&bits("1"))
+            .unwrap();
+        assert_eq!(space.free_vars().len(), 69);
+        assert_eq!(space.num_solutions(), None);
+        assert!(space.solutions().is_none());
+        assert_eq!(space.min_weight_solution(), None);
+
+        // 27 buttons for one light is past the search limit, though still countable.
+        let buttons = vec![Gf2Vector::from_iter([true]); 27];
+        let space = Gf2Matrix::from_columns(&buttons, 1)
+            .solve(&bits("1"))
+            .unwrap();
+        assert_eq!(space.num_solutions(), Some(1 << 26));
+        assert_eq!(space.min_weight_solution(), None);
+    }
+}
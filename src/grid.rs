@@ -1,12 +1,20 @@
 use bare_metal_modulo::*;
+use enum_iterator::all;
+use itertools::Itertools;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use std::{
     collections::{BTreeMap, BTreeSet, HashMap},
     fmt::{Debug, Display},
     str::FromStr,
 };
 
-use crate::multidim::{
-    DirType, Position, RingIterator, RowMajorPositionIterator, map_width_height, to_map,
+use crate::{
+    automaton::AutomatonGrid,
+    multidim::{
+        Dir, DirType, Point, Position, RingIterator, RowMajorPositionIterator, map_width_height,
+        to_map,
+    },
 };
 
 pub type GridDigitWorld = GridWorld<ModNumC<u8, 10>>;
@@ -150,6 +158,30 @@ impl<V: Copy + Clone + Eq + PartialEq> GridWorld<V> {
             .collect()
     }
 
+    /// Positions from `p` (exclusive) in direction `dir` until leaving the grid or reaching
+    /// a cell satisfying `blocker`. A blocking cell is included as the final position.
+    pub fn cast<D: DirType, B: Fn(V) -> bool>(
+        &self,
+        p: Position,
+        dir: D,
+        blocker: B,
+    ) -> Vec<Position> {
+        let mut result = vec![];
+        for q in dir.iter_from(p).skip(1).take_while(|q| self.in_bounds(*q)) {
+            result.push(q);
+            if blocker(self.value(q).unwrap()) {
+                break;
+            }
+        }
+        result
+    }
+
+    /// Total number of cells visible from `p` in every direction of `D`, counting any
+    /// cells that block the view.
+    pub fn visible_from<D: DirType, B: Fn(V) -> bool>(&self, p: Position, blocker: B) -> usize {
+        all::<D>().map(|d| self.cast(p, d, &blocker).len()).sum()
+    }
+
     pub fn get(&self, col: usize, row: usize) -> Option<V> {
         self.map.get(col).and_then(|c| c.get(row).copied())
     }
@@ -200,6 +232,40 @@ impl<V: Copy + Clone + Eq + PartialEq> GridWorld<V> {
     }
 }
 
+#[cfg(feature = "parallel")]
+impl<V: Copy + Clone + Eq + PartialEq + Send + Sync> GridWorld<V> {
+    pub fn par_map<F: Fn(Position, &V) -> V + Sync>(&self, mapper: F) -> Self {
+        Self {
+            map: self
+                .map
+                .par_iter()
+                .enumerate()
+                .map(|(x, column)| {
+                    column
+                        .iter()
+                        .enumerate()
+                        .map(|(y, v)| mapper(Position::from_usize(x, y), v))
+                        .collect()
+                })
+                .collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    pub fn par_position_iter(&self) -> impl ParallelIterator<Item = Position> {
+        let width = self.width;
+        (0..self.width * self.height)
+            .into_par_iter()
+            .map(move |i| Position::from_usize(i % width, i / width))
+    }
+
+    pub fn par_position_value_iter(&self) -> impl ParallelIterator<Item = (Position, V)> + '_ {
+        self.par_position_iter()
+            .map(|p| (p, self.value(p).unwrap()))
+    }
+}
+
 impl<V: CharDisplay + Copy + Eq + PartialEq> Display for GridWorld<V> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for p in self.position_iter() {
@@ -212,19 +278,39 @@ impl<V: CharDisplay + Copy + Eq + PartialEq> Display for GridWorld<V> {
     }
 }
 
+impl<V: Copy + Clone + Eq + PartialEq> AutomatonGrid<V> for GridWorld<V> {
+    fn cell(&self, p: Position) -> Option<V> {
+        if self.in_bounds(p) {
+            self.value(p)
+        } else {
+            None
+        }
+    }
+
+    fn set_cell(&mut self, p: Position, value: V) {
+        self.update(p, value);
+    }
+
+    fn candidates(&self) -> Vec<Position> {
+        self.position_iter().collect()
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct InfiniteGrid<V: Copy + Clone + Debug + Default + Display> {
     map: BTreeMap<Position, V>,
+    bounds: Option<(Position, Position)>,
 }
 
 impl<V: Copy + Clone + Debug + Default + Display> Display for InfiniteGrid<V> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let ((x_start, y_start), (x_end, y_end)) = self.bounding_box();
-        for y in y_start..=y_end {
-            for x in x_start..=x_end {
-                write!(f, "{}", self.get(x, y))?;
+        if let Some(((x_start, y_start), (x_end, y_end))) = self.bounding_box() {
+            for y in y_start..=y_end {
+                for x in x_start..=x_end {
+                    write!(f, "{}", self.get(x, y))?;
+                }
+                writeln!(f)?;
             }
-            write!(f, "\n")?
         }
         Ok(())
     }
@@ -237,6 +323,19 @@ impl<V: Copy + Clone + Debug + Default + Display> InfiniteGrid<V> {
 
     pub fn add_pos(&mut self, p: Position, value: V) {
         self.map.insert(p, value);
+        self.bounds =
+            Point::min_max_points(self.bounds.iter().flat_map(|(a, b)| [*a, *b]).chain([p]));
+    }
+
+    pub fn remove_pos(&mut self, p: Position) -> Option<V> {
+        let removed = self.map.remove(&p);
+        if let Some((ul, lr)) = self.bounds
+            && removed.is_some()
+            && (0..2).any(|i| p[i] == ul[i] || p[i] == lr[i])
+        {
+            self.bounds = Point::min_max_points(self.map.keys().copied());
+        }
+        removed
     }
 
     pub fn get(&self, x: isize, y: isize) -> V {
@@ -247,37 +346,212 @@ impl<V: Copy + Clone + Debug + Default + Display> InfiniteGrid<V> {
         self.add_pos(Position::from_isize((x, y)), value)
     }
 
+    pub fn remove(&mut self, x: isize, y: isize) -> Option<V> {
+        self.remove_pos(Position::from_isize((x, y)))
+    }
+
     pub fn move_square(&mut self, start: (isize, isize), movement: (isize, isize)) {
         let start = Position::from_isize(start);
         let offset = Position::from_isize(movement);
-        let value = self.map.remove(&start).unwrap_or_default();
+        let value = self.remove_pos(start).unwrap_or_default();
         self.add_pos(start + offset, value);
     }
 
-    pub fn bounding_box(&self) -> ((isize, isize), (isize, isize)) {
-        ((self.min_x(), self.min_y()), (self.max_x(), self.max_y()))
+    pub fn iter(&self) -> impl Iterator<Item = (Position, V)> + '_ {
+        self.map.iter().map(|(p, v)| (*p, *v))
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn bounding_box(&self) -> Option<((isize, isize), (isize, isize))> {
+        self.bounds.map(|(ul, lr)| ((ul[0], ul[1]), (lr[0], lr[1])))
     }
 
-    pub fn min_x(&self) -> isize {
-        self.map.keys().map(|k| k[0]).min().unwrap()
+    pub fn min_x(&self) -> Option<isize> {
+        self.bounds.map(|(ul, _)| ul[0])
     }
 
-    pub fn max_x(&self) -> isize {
-        self.map.keys().map(|k| k[0]).max().unwrap()
+    pub fn max_x(&self) -> Option<isize> {
+        self.bounds.map(|(_, lr)| lr[0])
     }
 
-    pub fn min_y(&self) -> isize {
-        self.map.keys().map(|k| k[1]).min().unwrap()
+    pub fn min_y(&self) -> Option<isize> {
+        self.bounds.map(|(ul, _)| ul[1])
     }
 
-    pub fn max_y(&self) -> isize {
-        self.map.keys().map(|k| k[1]).max().unwrap()
+    pub fn max_y(&self) -> Option<isize> {
+        self.bounds.map(|(_, lr)| lr[1])
+    }
+}
+
+/// Shifts the grid so that the upper-left corner of its bounding box is at the origin.
+/// Cells within the bounding box that were never stored hold the default value.
+impl<V: Copy + Clone + Debug + Default + Display + Eq> From<&InfiniteGrid<V>> for GridWorld<V> {
+    fn from(value: &InfiniteGrid<V>) -> Self {
+        match value.bounds {
+            None => GridWorld::new(0, 0, V::default()),
+            Some((ul, lr)) => {
+                let mut result = GridWorld::new(
+                    (lr[0] - ul[0] + 1) as usize,
+                    (lr[1] - ul[1] + 1) as usize,
+                    V::default(),
+                );
+                for (p, v) in value.iter() {
+                    result.update(p - ul, v);
+                }
+                result
+            }
+        }
+    }
+}
+
+impl<V: Copy + Clone + Debug + Default + Display + Eq> From<&GridWorld<V>> for InfiniteGrid<V> {
+    fn from(value: &GridWorld<V>) -> Self {
+        let mut result = Self::default();
+        for (p, v) in value.position_value_iter() {
+            result.add_pos(p, v);
+        }
+        result
+    }
+}
+
+impl<V: Copy + Clone + Debug + Default + Display + Eq> AutomatonGrid<V> for InfiniteGrid<V> {
+    fn cell(&self, p: Position) -> Option<V> {
+        Some(self.get_pos(p))
+    }
+
+    fn set_cell(&mut self, p: Position, value: V) {
+        if value == V::default() {
+            self.remove_pos(p);
+        } else {
+            self.add_pos(p, value);
+        }
+    }
+
+    fn candidates(&self) -> Vec<Position> {
+        let mut result = BTreeSet::new();
+        for (p, v) in self.map.iter() {
+            result.insert(*p);
+            if *v != V::default() {
+                for d in all::<Dir>() {
+                    result.insert(d.neighbor(*p));
+                }
+            }
+        }
+        result.into_iter().collect()
+    }
+}
+
+/// Sparse storage over `S`-dimensional integer points. Unstored cells hold the default value.
+#[derive(Clone, Debug, Default)]
+pub struct SparseGrid<V: Copy + Clone + Debug + Default + Display, const S: usize> {
+    map: BTreeMap<Point<isize, S>, V>,
+}
+
+impl<V: Copy + Clone + Debug + Default + Display, const S: usize> SparseGrid<V, S> {
+    pub fn get(&self, p: &Point<isize, S>) -> V {
+        self.map.get(p).copied().unwrap_or_default()
+    }
+
+    pub fn add(&mut self, p: Point<isize, S>, value: V) {
+        self.map.insert(p, value);
+    }
+
+    pub fn remove(&mut self, p: &Point<isize, S>) -> Option<V> {
+        self.map.remove(p)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point<isize, S>, V)> + '_ {
+        self.map.iter().map(|(p, v)| (*p, *v))
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn neighbors(&self, p: &Point<isize, S>) -> impl Iterator<Item = (Point<isize, S>, V)> {
+        p.all_neighbors().into_iter().map(|n| (n, self.get(&n)))
+    }
+
+    pub fn min_max_points(&self) -> Option<(Point<isize, S>, Point<isize, S>)> {
+        Point::min_max_points(self.map.keys().copied())
+    }
+
+    pub fn bounding_box(&self) -> Option<Vec<Point<isize, S>>> {
+        Point::bounding_box(self.map.keys().copied())
+    }
+
+    /// Renders the x-y plane whose remaining coordinates are `fixed`, over the x-y extent
+    /// of the whole grid. A one-dimensional grid renders as a single row.
+    pub fn slice_string(&self, fixed: &[isize]) -> String {
+        assert!(
+            fixed.len() <= S.saturating_sub(2),
+            "a {S}-dimensional grid has {} coordinates beyond x and y, not {}",
+            S.saturating_sub(2),
+            fixed.len()
+        );
+        let mut result = String::new();
+        if let Some((ul, lr)) = self.min_max_points() {
+            let mut p = Point::default();
+            for (i, c) in fixed.iter().enumerate() {
+                p[i + 2] = *c;
+            }
+            let ys = if S >= 2 { ul[1]..=lr[1] } else { 0..=0 };
+            for y in ys {
+                if S >= 2 {
+                    p[1] = y;
+                }
+                for x in ul[0]..=lr[0] {
+                    p[0] = x;
+                    result.push_str(format!("{}", self.get(&p)).as_str());
+                }
+                result.push('\n');
+            }
+        }
+        result
+    }
+}
+
+impl<V: Copy + Clone + Debug + Default + Display, const S: usize> Display for SparseGrid<V, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some((ul, lr)) = self.min_max_points() {
+            if S <= 2 {
+                return write!(f, "{}", self.slice_string(&[]));
+            }
+            let ranges = (2..S).map(|i| ul[i]..=lr[i]).collect::<Vec<_>>();
+            for fixed in ranges.into_iter().multi_cartesian_product() {
+                let label = fixed
+                    .iter()
+                    .enumerate()
+                    .map(|(i, c)| match i {
+                        0 => format!("z={c}"),
+                        1 => format!("w={c}"),
+                        _ => format!("[{}]={c}", i + 2),
+                    })
+                    .join(", ");
+                writeln!(f, "{label}")?;
+                writeln!(f, "{}", self.slice_string(&fixed))?;
+            }
+        }
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::GridCharWorld;
+    use crate::multidim::{Dir, ManhattanDir, Point, Position};
+
+    use super::{GridCharWorld, GridWorld, InfiniteGrid, SparseGrid};
 
     #[test]
     fn test_grid_read() {
@@ -290,4 +564,154 @@ mod tests {
         let maze = maze_str.parse::<GridCharWorld>().unwrap();
         assert_eq!(maze_str, format!("{maze}").as_str());
     }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_par_map() {
+        use rayon::prelude::*;
+
+        let maze = ".....##
+###.###
+#.....#
+#.#####
+#......"
+            .parse::<GridCharWorld>()
+            .unwrap();
+        let flip = |p: crate::multidim::Position, v: &char| {
+            if *v == '#' || p[0] == p[1] { '.' } else { '#' }
+        };
+        assert_eq!(maze.map(flip), maze.par_map(flip));
+
+        let mut positions = maze.par_position_iter().collect::<Vec<_>>();
+        positions.sort_by_key(|p| (p[1], p[0]));
+        assert_eq!(positions, maze.position_iter().collect::<Vec<_>>());
+        assert_eq!(
+            maze.par_position_value_iter()
+                .filter(|(_, v)| *v == '#')
+                .count(),
+            maze.position_value_iter()
+                .filter(|(_, v)| *v == '#')
+                .count()
+        );
+    }
+
+    #[test]
+    fn test_infinite_grid() {
+        let mut grid = InfiniteGrid::default();
+        assert!(grid.is_empty());
+        assert_eq!(grid.bounding_box(), None);
+        assert_eq!(format!("{grid}"), "");
+
+        for (x, y, c) in [(-1, 2, 'a'), (3, -4, 'b'), (0, 0, 'c')] {
+            grid.add(x, y, c);
+        }
+        assert_eq!(grid.len(), 3);
+        assert_eq!(grid.bounding_box(), Some(((-1, -4), (3, 2))));
+
+        assert_eq!(grid.remove(3, -4), Some('b'));
+        assert_eq!(grid.remove(3, -4), None);
+        assert_eq!(grid.bounding_box(), Some(((-1, 0), (0, 2))));
+        assert_eq!(
+            grid.iter()
+                .map(|(p, c)| (p[0], p[1], c))
+                .collect::<Vec<_>>(),
+            vec![(-1, 2, 'a'), (0, 0, 'c')]
+        );
+
+        grid.move_square((0, 0), (2, 1));
+        assert_eq!(grid.bounding_box(), Some(((-1, 1), (2, 2))));
+        assert_eq!(grid.remove(-1, 2), Some('a'));
+        assert_eq!(grid.remove(2, 1), Some('c'));
+        assert_eq!(grid.bounding_box(), None);
+    }
+
+    #[test]
+    fn test_infinite_grid_conversion() {
+        let world = "#..
+.#.
+..#"
+        .parse::<GridCharWorld>()
+        .unwrap();
+        let grid = InfiniteGrid::from(&world);
+        assert_eq!(grid.len(), 9);
+        assert_eq!(GridWorld::from(&grid), world);
+
+        let mut sparse = InfiniteGrid::default();
+        sparse.add(5, -2, '#');
+        sparse.add(6, -1, '#');
+        let dense = GridWorld::from(&sparse);
+        assert_eq!((dense.width(), dense.height()), (2, 2));
+        assert_eq!(dense.positions_for('#').len(), 2);
+        assert_eq!(dense.get(1, 0), Some(char::default()));
+    }
+
+    #[test]
+    fn test_sparse_grid() {
+        let mut grid = SparseGrid::<u8, 3>::default();
+        assert_eq!(grid.bounding_box(), None);
+        for (x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+            grid.add(Point::new([x, y, 0]), 1);
+        }
+        grid.add(Point::new([0, 0, 1]), 2);
+        assert_eq!(grid.len(), 6);
+        assert_eq!(grid.bounding_box().unwrap().len(), 8);
+        assert_eq!(
+            grid.min_max_points(),
+            Some((Point::new([0, 0, 0]), Point::new([2, 2, 1])))
+        );
+
+        let center = Point::new([1, 1, 0]);
+        assert_eq!(grid.neighbors(&center).count(), 26);
+        assert_eq!(grid.neighbors(&center).filter(|(_, v)| *v == 1).count(), 5);
+
+        assert_eq!(grid.slice_string(&[0]), "010\n001\n111\n");
+        assert_eq!(
+            format!("{grid}"),
+            "z=0\n010\n001\n111\n\nz=1\n200\n000\n000\n\n"
+        );
+        assert_eq!(grid.remove(&Point::new([0, 0, 1])), Some(2));
+        assert_eq!(grid.remove(&Point::new([0, 0, 1])), None);
+        assert_eq!(grid.len(), 5);
+
+        let mut line = SparseGrid::<u8, 1>::default();
+        line.add(Point::new([-1]), 3);
+        line.add(Point::new([1]), 4);
+        assert_eq!(format!("{line}"), "304\n");
+    }
+
+    #[test]
+    fn test_cast() {
+        let world = "..#..
+.....
+#.*..
+.....
+..#.."
+            .parse::<GridCharWorld>()
+            .unwrap();
+        let center = world.any_position_for('*');
+        let blocked = |c| c == '#';
+        let as_pairs = |ps: Vec<Position>| ps.iter().map(|p| (p[0], p[1])).collect::<Vec<_>>();
+        assert_eq!(
+            as_pairs(world.cast(center, ManhattanDir::N, blocked)),
+            vec![(2, 1), (2, 0)]
+        );
+        assert_eq!(
+            as_pairs(world.cast(center, ManhattanDir::E, blocked)),
+            vec![(3, 2), (4, 2)]
+        );
+        assert_eq!(
+            as_pairs(world.cast(center, ManhattanDir::W, blocked)),
+            vec![(1, 2), (0, 2)]
+        );
+        assert_eq!(
+            as_pairs(world.cast(center, Dir::Nw, blocked)),
+            vec![(1, 1), (0, 0)]
+        );
+        assert_eq!(
+            world.cast(Position::default(), ManhattanDir::N, blocked),
+            vec![]
+        );
+        assert_eq!(world.visible_from::<ManhattanDir, _>(center, blocked), 8);
+        assert_eq!(world.visible_from::<Dir, _>(center, blocked), 16);
+    }
 }
@@ -0,0 +1,653 @@
+use std::{
+    collections::BTreeMap,
+    iter::Sum,
+    ops::{Add, Mul},
+};
+
+use anyhow::bail;
+use num::{BigInt, BigRational, Integer, One, Signed, ToPrimitive, Zero};
+
+/// A variable of an `IntProgram`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct IntVar(usize);
+
+/// A sum of variables with integer coefficients.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LinearExpr {
+    terms: BTreeMap<IntVar, i64>,
+}
+
+impl LinearExpr {
+    pub fn coefficient(&self, var: IntVar) -> i64 {
+        self.terms.get(&var).copied().unwrap_or(0)
+    }
+
+    pub fn terms(&self) -> impl Iterator<Item = (IntVar, i64)> + '_ {
+        self.terms.iter().map(|(v, c)| (*v, *c))
+    }
+
+    fn add_term(&mut self, var: IntVar, coefficient: i64) {
+        *self.terms.entry(var).or_insert(0) += coefficient;
+    }
+}
+
+impl From<IntVar> for LinearExpr {
+    fn from(var: IntVar) -> Self {
+        var * 1
+    }
+}
+
+impl Mul<i64> for IntVar {
+    type Output = LinearExpr;
+
+    fn mul(self, rhs: i64) -> Self::Output {
+        let mut result = LinearExpr::default();
+        result.add_term(self, rhs);
+        result
+    }
+}
+
+impl<E: Into<LinearExpr>> Add<E> for LinearExpr {
+    type Output = LinearExpr;
+
+    fn add(mut self, rhs: E) -> Self::Output {
+        for (var, coefficient) in rhs.into().terms {
+            self.add_term(var, coefficient);
+        }
+        self
+    }
+}
+
+impl<E: Into<LinearExpr>> Add<E> for IntVar {
+    type Output = LinearExpr;
+
+    fn add(self, rhs: E) -> Self::Output {
+        LinearExpr::from(self) + rhs
+    }
+}
+
+impl<E: Into<LinearExpr>> Sum<E> for LinearExpr {
+    fn sum<I: Iterator<Item = E>>(iter: I) -> Self {
+        iter.fold(LinearExpr::default(), |total, e| total + e)
+    }
+}
+
+/// A small integer linear program: minimize a linear objective over integer
+/// variables subject to linear equalities and per-variable bounds.
+///
+/// Solved by branch and bound. Each node solves the linear relaxation exactly with
+/// the simplex method over `BigRational`, so intermediate values cannot overflow,
+/// then splits on a fractional variable. Nodes whose relaxation cannot beat the best
+/// integer solution so far are pruned. The number of nodes is exponential in the
+/// worst case, but stays small when the relaxation is nearly integral, as it is for
+/// systems like day 10's buttons and counters. Splitting along an unbounded direction
+/// need never end, so the search gives up after `NODE_LIMIT` nodes by default.
+#[derive(Clone, Debug, Default)]
+pub struct IntProgram {
+    lower: Vec<Option<i64>>,
+    upper: Vec<Option<i64>>,
+    equalities: Vec<(LinearExpr, i64)>,
+    /// Always minimized; a maximization is stored negated.
+    objective: LinearExpr,
+    maximizing: bool,
+    node_limit: Option<usize>,
+}
+
+impl IntProgram {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn int_var(&mut self) -> IntVar {
+        self.lower.push(None);
+        self.upper.push(None);
+        IntVar(self.lower.len() - 1)
+    }
+
+    pub fn at_least(&mut self, var: IntVar, lower: i64) {
+        self.lower[var.0] = Some(self.lower[var.0].map_or(lower, |l| l.max(lower)));
+    }
+
+    pub fn at_most(&mut self, var: IntVar, upper: i64) {
+        self.upper[var.0] = Some(self.upper[var.0].map_or(upper, |u| u.min(upper)));
+    }
+
+    pub fn assert_eq<E: Into<LinearExpr>>(&mut self, expr: E, value: i64) {
+        self.equalities.push((expr.into(), value));
+    }
+
+    pub fn minimize<E: Into<LinearExpr>>(&mut self, objective: E) {
+        self.objective = objective.into();
+        self.maximizing = false;
+    }
+
+    pub fn maximize<E: Into<LinearExpr>>(&mut self, objective: E) {
+        self.objective = objective.into().terms().map(|(v, c)| v * -c).sum();
+        self.maximizing = true;
+    }
+
+    /// Overrides `NODE_LIMIT` for this program.
+    pub fn node_limit(&mut self, limit: usize) {
+        self.node_limit = Some(limit);
+    }
+
+    fn num_vars(&self) -> usize {
+        self.lower.len()
+    }
+
+    /// Finds an optimal assignment, or `None` if the constraints are unsatisfiable.
+    /// Fails if the objective is unbounded, the optimum does not fit in `i64`, or the
+    /// search exceeds its node limit.
+    pub fn solve(&self) -> anyhow::Result<Option<IntModel>> {
+        // An equality whose coefficients share a factor that its right-hand side lacks
+        // has no integer solutions, though its relaxation may have plenty.
+        for (expr, value) in self.equalities.iter() {
+            let gcd = expr
+                .terms()
+                .fold(BigInt::zero(), |g, (_, c)| g.gcd(&BigInt::from(c)));
+            if !BigInt::from(*value).is_multiple_of(&gcd) {
+                return Ok(None);
+            }
+        }
+        let bounds = |bs: &[Option<i64>]| bs.iter().map(|b| b.map(BigInt::from)).collect();
+        let mut pending: Vec<(Bounds, Bounds)> = vec![(bounds(&self.lower), bounds(&self.upper))];
+        let mut best: Option<(BigInt, Vec<BigInt>)> = None;
+        let limit = self.node_limit.unwrap_or(NODE_LIMIT);
+        let mut nodes = 0;
+        while let Some((lower, upper)) = pending.pop() {
+            nodes += 1;
+            if nodes > limit {
+                bail!("Gave up after {limit} branch and bound nodes");
+            }
+            let (value, values) = match self.relax(&lower, &upper) {
+                Relaxation::Infeasible => continue,
+                Relaxation::Unbounded => bail!("The objective is unbounded"),
+                Relaxation::Optimal(value, values) => (value, values),
+            };
+            // Integer coefficients give integer objectives, so the bound rounds up.
+            if best
+                .as_ref()
+                .is_some_and(|(b, _)| value.ceil().to_integer() >= *b)
+            {
+                continue;
+            }
+            let half = BigRational::new(BigInt::one(), BigInt::from(2));
+            let fractional = values
+                .iter()
+                .enumerate()
+                .filter(|(_, x)| !x.is_integer())
+                .min_by_key(|(_, x)| (x.fract() - &half).abs());
+            match fractional {
+                None => {
+                    best = Some((
+                        value.to_integer(),
+                        values.iter().map(|x| x.to_integer()).collect(),
+                    ))
+                }
+                Some((v, x)) => {
+                    let floor = x.floor().to_integer();
+                    let mut below = (lower.clone(), upper.clone());
+                    below.1[v] = Some(floor.clone());
+                    let mut above = (lower, upper);
+                    above.0[v] = Some(floor + 1);
+                    // Explore the side nearer the relaxed value first.
+                    if x.fract() < half {
+                        pending.extend([above, below]);
+                    } else {
+                        pending.extend([below, above]);
+                    }
+                }
+            }
+        }
+        best.map(|(objective, values)| {
+            let objective = if self.maximizing {
+                -objective
+            } else {
+                objective
+            };
+            Ok(IntModel {
+                values: values
+                    .iter()
+                    .enumerate()
+                    .map(|(v, x)| fit(x, || format!("Variable {v}")))
+                    .collect::<anyhow::Result<_>>()?,
+                objective: fit(&objective, || "The objective".to_string())?,
+            })
+        })
+        .transpose()
+    }
+
+    /// Solves the linear relaxation within the given bounds. Each variable becomes an
+    /// offset plus a signed combination of non-negative columns: `lower + y`,
+    /// `upper - y`, or `y+ - y-` when unbounded, with a slack column for an upper
+    /// bound on top of a lower one.
+    fn relax(&self, lower: &[Option<BigInt>], upper: &[Option<BigInt>]) -> Relaxation {
+        let mut num_columns = 0;
+        let mut substitutions = vec![];
+        let mut bound_rows = vec![];
+        for v in 0..self.num_vars() {
+            let mut column = || {
+                num_columns += 1;
+                num_columns - 1
+            };
+            substitutions.push(match (&lower[v], &upper[v]) {
+                (Some(l), Some(u)) if u < l => return Relaxation::Infeasible,
+                (Some(l), Some(u)) => {
+                    let (y, slack) = (column(), column());
+                    bound_rows.push((vec![(y, BigInt::one()), (slack, BigInt::one())], u - l));
+                    (l.clone(), vec![(y, BigInt::one())])
+                }
+                (Some(l), None) => (l.clone(), vec![(column(), BigInt::one())]),
+                (None, Some(u)) => (u.clone(), vec![(column(), -BigInt::one())]),
+                (None, None) => (
+                    BigInt::zero(),
+                    vec![(column(), BigInt::one()), (column(), -BigInt::one())],
+                ),
+            });
+        }
+        // Rewrites `expr` over the columns, returning its terms and constant.
+        let substitute = |expr: &LinearExpr| {
+            let mut terms = vec![BigInt::zero(); num_columns];
+            let mut constant = BigInt::zero();
+            for (var, c) in expr.terms() {
+                let (offset, columns) = &substitutions[var.0];
+                constant += offset * c;
+                for (col, sign) in columns {
+                    terms[*col] += sign * c;
+                }
+            }
+            (terms, constant)
+        };
+
+        let mut rows = vec![];
+        let mut rhs = vec![];
+        for (expr, value) in self.equalities.iter() {
+            let (terms, constant) = substitute(expr);
+            rows.push(terms);
+            rhs.push(BigInt::from(*value) - constant);
+        }
+        for (terms, bound) in bound_rows {
+            let mut row = vec![BigInt::zero(); num_columns];
+            for (col, c) in terms {
+                row[col] = c;
+            }
+            rows.push(row);
+            rhs.push(bound);
+        }
+        let (costs, constant) = substitute(&self.objective);
+        let ratio = |n: &BigInt| BigRational::from_integer(n.clone());
+        match Tableau::minimize(
+            rows.iter()
+                .map(|row| row.iter().map(ratio).collect())
+                .collect(),
+            rhs.iter().map(ratio).collect(),
+            costs.iter().map(ratio).collect(),
+        ) {
+            Relaxation::Optimal(value, columns) => Relaxation::Optimal(
+                value + ratio(&constant),
+                substitutions
+                    .iter()
+                    .map(|(offset, terms)| {
+                        terms.iter().fold(ratio(offset), |x, (col, sign)| {
+                            x + &columns[*col] * ratio(sign)
+                        })
+                    })
+                    .collect(),
+            ),
+            other => other,
+        }
+    }
+}
+
+/// The most relaxations `IntProgram::solve` will try before giving up.
+pub const NODE_LIMIT: usize = 100_000;
+
+type Bounds = Vec<Option<BigInt>>;
+
+fn fit<F: Fn() -> String>(n: &BigInt, name: F) -> anyhow::Result<i64> {
+    match n.to_i64() {
+        Some(n) => Ok(n),
+        None => bail!("{} is {n}, which does not fit in an i64", name()),
+    }
+}
+
+/// An optimal assignment found by `IntProgram::solve`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IntModel {
+    values: Vec<i64>,
+    objective: i64,
+}
+
+impl IntModel {
+    pub fn eval(&self, var: IntVar) -> i64 {
+        self.values[var.0]
+    }
+
+    pub fn objective(&self) -> i64 {
+        self.objective
+    }
+}
+
+/// The outcome of a linear program: its minimum and the values achieving it.
+enum Relaxation {
+    Infeasible,
+    Unbounded,
+    Optimal(BigRational, Vec<BigRational>),
+}
+
+/// A dense simplex tableau for minimizing over equalities and non-negative columns.
+struct Tableau {
+    /// Each row holds its coefficients followed by its right-hand side.
+    rows: Vec<Vec<BigRational>>,
+    /// Reduced costs, followed by the negated objective value.
+    costs: Vec<BigRational>,
+    basis: Vec<usize>,
+}
+
+impl Tableau {
+    /// Minimizes `costs · y` subject to `rows · y = rhs` and `y >= 0`, using two
+    /// phases and Bland's rule so that it cannot cycle.
+    fn minimize(
+        rows: Vec<Vec<BigRational>>,
+        rhs: Vec<BigRational>,
+        costs: Vec<BigRational>,
+    ) -> Relaxation {
+        let (m, n) = (rows.len(), costs.len());
+        // Phase one starts from an artificial basis and minimizes the artificials.
+        let rows = rows
+            .into_iter()
+            .zip(rhs)
+            .enumerate()
+            .map(|(i, (mut row, b))| {
+                let flip = b.is_negative();
+                row.extend((0..m).map(|j| BigRational::from_integer((i == j).into())));
+                row.push(b);
+                if flip {
+                    for (j, entry) in row.iter_mut().enumerate() {
+                        if j < n || j == n + m {
+                            *entry = -entry.clone();
+                        }
+                    }
+                }
+                row
+            })
+            .collect::<Vec<_>>();
+        let phase_one_costs = (0..=n + m)
+            .map(|j| {
+                if (n..n + m).contains(&j) {
+                    BigRational::zero()
+                } else {
+                    -rows.iter().map(|row| &row[j]).sum::<BigRational>()
+                }
+            })
+            .collect();
+        let mut tableau = Self {
+            rows,
+            costs: phase_one_costs,
+            basis: (n..n + m).collect(),
+        };
+        tableau.optimize(n + m);
+        if !tableau.costs[n + m].is_zero() {
+            return Relaxation::Infeasible;
+        }
+
+        // Drive the artificials out of the basis, dropping rows that are redundant.
+        let mut i = 0;
+        while i < tableau.rows.len() {
+            if tableau.basis[i] >= n {
+                match (0..n).find(|j| !tableau.rows[i][*j].is_zero()) {
+                    Some(j) => tableau.pivot(i, j),
+                    None => {
+                        tableau.rows.remove(i);
+                        tableau.basis.remove(i);
+                        continue;
+                    }
+                }
+            }
+            i += 1;
+        }
+        for row in tableau.rows.iter_mut() {
+            row.drain(n..n + m);
+        }
+
+        let mut reduced = costs;
+        reduced.push(BigRational::zero());
+        for (row, b) in tableau.rows.iter().zip(tableau.basis.iter()) {
+            let weight = reduced[*b].clone();
+            for (r, entry) in reduced.iter_mut().zip(row.iter()) {
+                *r -= &weight * entry;
+            }
+        }
+        tableau.costs = reduced;
+        if !tableau.optimize(n) {
+            return Relaxation::Unbounded;
+        }
+        let mut values = vec![BigRational::zero(); n];
+        for (row, b) in tableau.rows.iter().zip(tableau.basis.iter()) {
+            values[*b] = row[row.len() - 1].clone();
+        }
+        Relaxation::Optimal(-tableau.costs[n].clone(), values)
+    }
+
+    /// Pivots until no column before `limit` has a negative reduced cost. Returns
+    /// false if the objective is unbounded.
+    fn optimize(&mut self, limit: usize) -> bool {
+        while let Some(col) = (0..limit).find(|j| self.costs[*j].is_negative()) {
+            let last = self.costs.len() - 1;
+            let mut leaving: Option<(usize, BigRational)> = None;
+            for (i, row) in self.rows.iter().enumerate() {
+                if row[col].is_positive() {
+                    let ratio = &row[last] / &row[col];
+                    if leaving.as_ref().is_none_or(|(l, best)| {
+                        ratio < *best || (ratio == *best && self.basis[i] < self.basis[*l])
+                    }) {
+                        leaving = Some((i, ratio));
+                    }
+                }
+            }
+            match leaving {
+                Some((i, _)) => self.pivot(i, col),
+                None => return false,
+            }
+        }
+        true
+    }
+
+    fn pivot(&mut self, row: usize, col: usize) {
+        let pivot = self.rows[row][col].clone();
+        for entry in self.rows[row].iter_mut() {
+            *entry /= &pivot;
+        }
+        let pivot_row = self.rows[row].clone();
+        let eliminate = |target: &mut Vec<BigRational>| {
+            let factor = target[col].clone();
+            if !factor.is_zero() {
+                for (entry, p) in target.iter_mut().zip(pivot_row.iter()) {
+                    *entry -= &factor * p;
+                }
+            }
+        };
+        for (i, other) in self.rows.iter_mut().enumerate() {
+            if i != row {
+                eliminate(other);
+            }
+        }
+        eliminate(&mut self.costs);
+        self.basis[row] = col;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::{IntProgram, IntVar, LinearExpr};
+
+    fn joltage_presses(buttons: &[&[usize]], joltages: &[i64]) -> i64 {
+        let mut program = IntProgram::new();
+        let vars = buttons
+            .iter()
+            .map(|_| program.int_var())
+            .collect::<Vec<IntVar>>();
+        for var in vars.iter() {
+            program.at_least(*var, 0);
+        }
+        for (i, joltage) in joltages.iter().enumerate() {
+            program.assert_eq(
+                buttons
+                    .iter()
+                    .zip(vars.iter())
+                    .filter(|(b, _)| b.contains(&i))
+                    .map(|(_, v)| *v)
+                    .sum::<LinearExpr>(),
+                *joltage,
+            );
+        }
+        program.minimize(vars.iter().copied().sum::<LinearExpr>());
+        let model = program.solve().unwrap().unwrap();
+        assert_eq!(
+            model.objective(),
+            vars.iter().map(|v| model.eval(*v)).sum::<i64>()
+        );
+        model.objective()
+    }
+
+    #[test]
+    fn test_joltages() {
+        assert_eq!(
+            joltage_presses(
+                &[&[3], &[1, 3], &[2], &[2, 3], &[0, 2], &[0, 1]],
+                &[3, 5, 4, 7]
+            ),
+            10
+        );
+        assert_eq!(
+            joltage_presses(
+                &[&[0, 2, 3, 4], &[2, 3], &[0, 4], &[0, 1, 2], &[1, 2, 3, 4]],
+                &[7, 5, 12, 7, 2]
+            ),
+            12
+        );
+        assert_eq!(
+            joltage_presses(
+                &[&[0, 1, 2, 3, 4], &[0, 3, 4], &[0, 1, 2, 4, 5], &[1, 2]],
+                &[10, 11, 11, 5, 10, 5]
+            ),
+            11
+        );
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut seed = 12345_u64;
+        let mut random = |n: u64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) % n
+        };
+        for _ in 0..30 {
+            let buttons = (0..5)
+                .map(|_| (0..4).filter(|_| random(2) == 0).collect::<Vec<usize>>())
+                .collect::<Vec<_>>();
+            let presses = (0..5).map(|_| random(4) as i64).collect::<Vec<_>>();
+            let joltages = (0..4)
+                .map(|i| {
+                    buttons
+                        .iter()
+                        .zip(presses.iter())
+                        .filter(|(b, _)| b.contains(&i))
+                        .map(|(_, p)| p)
+                        .sum()
+                })
+                .collect::<Vec<i64>>();
+            let brute = (0..5)
+                .map(|_| 0..=*joltages.iter().max().unwrap())
+                .multi_cartesian_product()
+                .filter(|counts| {
+                    (0..4).all(|i| {
+                        buttons
+                            .iter()
+                            .zip(counts.iter())
+                            .filter(|(b, _)| b.contains(&i))
+                            .map(|(_, c)| c)
+                            .sum::<i64>()
+                            == joltages[i]
+                    })
+                })
+                .map(|counts| counts.iter().sum::<i64>())
+                .min()
+                .unwrap();
+            let buttons = buttons.iter().map(|b| b.as_slice()).collect::<Vec<_>>();
+            assert_eq!(joltage_presses(&buttons, &joltages), brute, "{buttons:?}");
+        }
+    }
+
+    #[test]
+    fn test_builder() {
+        let mut program = IntProgram::new();
+        let x = program.int_var();
+        let y = program.int_var();
+        program.at_least(x, 0);
+        program.at_most(x, 10);
+        program.at_least(y, -3);
+        program.assert_eq(x * 2 + y * 3, 12);
+        program.maximize(x + y);
+        let model = program.solve().unwrap().unwrap();
+        assert_eq!((model.eval(x), model.eval(y)), (9, -2));
+        assert_eq!(model.objective(), 7);
+
+        let mut free = IntProgram::new();
+        let x = free.int_var();
+        let y = free.int_var();
+        free.assert_eq(x + y * -1, 0);
+        free.assert_eq(x * 3 + y, 8);
+        let model = free.solve().unwrap().unwrap();
+        assert_eq!((model.eval(x), model.eval(y)), (2, 2));
+        let mut unbounded = IntProgram::new();
+        let x = unbounded.int_var();
+        let y = unbounded.int_var();
+        unbounded.assert_eq(x + y * -1, 0);
+        unbounded.minimize(x);
+        assert!(unbounded.solve().is_err());
+
+        let mut huge = IntProgram::new();
+        let x = huge.int_var();
+        huge.assert_eq(x * 2, i64::MAX - 1);
+        huge.minimize(x * 4);
+        assert!(huge.solve().is_err());
+
+        let mut infeasible = IntProgram::new();
+        let x = infeasible.int_var();
+        infeasible.at_least(x, 0);
+        infeasible.assert_eq(x * 2, 3);
+        assert_eq!(infeasible.solve().unwrap(), None);
+
+        // Feasible relaxations with no integer points, bounded and free.
+        let mut parity = IntProgram::new();
+        let x = parity.int_var();
+        let y = parity.int_var();
+        parity.at_least(x, 0);
+        parity.at_least(y, 0);
+        parity.assert_eq(x * 2 + y * -2, 1);
+        parity.minimize(x + y);
+        assert_eq!(parity.solve().unwrap(), None);
+        let mut parity = IntProgram::new();
+        let x = parity.int_var();
+        let y = parity.int_var();
+        parity.assert_eq(x * 2 + y * 2, 1);
+        assert_eq!(parity.solve().unwrap(), None);
+
+        // Each equality has integer solutions, but x cannot be both even and odd.
+        let mut endless = IntProgram::new();
+        let x = endless.int_var();
+        let y = endless.int_var();
+        let z = endless.int_var();
+        endless.at_least(x, 0);
+        endless.assert_eq(x + y * -2, 0);
+        endless.assert_eq(x + z * -2, 1);
+        endless.minimize(x);
+        endless.node_limit(1000);
+        assert!(endless.solve().is_err());
+    }
+}
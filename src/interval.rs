@@ -0,0 +1,462 @@
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+use anyhow::{anyhow, bail};
+use bare_metal_modulo::NumType;
+use itertools::Itertools;
+
+/// A range of integers including both endpoints.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Interval<N: NumType> {
+    start: N,
+    end: N,
+}
+
+impl<N: NumType> Interval<N> {
+    pub fn new(start: N, end: N) -> Self {
+        assert!(start <= end, "Interval start {start} exceeds end {end}");
+        Self { start, end }
+    }
+
+    pub fn start(&self) -> N {
+        self.start
+    }
+
+    pub fn end(&self) -> N {
+        self.end
+    }
+
+    /// The number of integers in the interval.
+    pub fn span(&self) -> N {
+        self.end - self.start + N::one()
+    }
+
+    pub fn contains(&self, value: N) -> bool {
+        self.start <= value && value <= self.end
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let start = self.start.max(other.start);
+        let end = self.end.min(other.end);
+        (start <= end).then_some(Self { start, end })
+    }
+}
+
+impl<N: NumType + FromStr> FromStr for Interval<N>
+where
+    <N as FromStr>::Err: 'static + Sync + Send + std::error::Error,
+{
+    type Err = anyhow::Error;
+
+    /// Parses `a-b`. A leading `-` belongs to the start, so `-5-3` is `-5` through `3`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let dash = s
+            .char_indices()
+            .skip(1)
+            .find(|(_, c)| *c == '-')
+            .map(|(i, _)| i)
+            .ok_or_else(|| anyhow!("No dash in {s}"))?;
+        let start = s[..dash].parse()?;
+        let end = s[dash + 1..].parse()?;
+        if start > end {
+            return Err(anyhow!("Interval start {start} exceeds end {end}"));
+        }
+        Ok(Self { start, end })
+    }
+}
+
+impl<N: NumType> Display for Interval<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+/// A set of integers stored as disjoint intervals, keyed by their starts. Overlapping
+/// and adjacent intervals are merged on insertion.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct IntervalSet<N: NumType> {
+    intervals: BTreeMap<N, N>,
+}
+
+impl<N: NumType> IntervalSet<N> {
+    pub fn new() -> Self {
+        Self {
+            intervals: BTreeMap::new(),
+        }
+    }
+
+    /// The number of disjoint intervals, not the number of integers.
+    pub fn len(&self) -> usize {
+        self.intervals.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Interval<N>> + '_ {
+        self.intervals
+            .iter()
+            .map(|(start, end)| Interval::new(*start, *end))
+    }
+
+    /// The number of integers in the set.
+    pub fn span(&self) -> N {
+        self.iter().fold(N::zero(), |total, i| total + i.span())
+    }
+
+    pub fn contains(&self, value: N) -> bool {
+        self.intervals
+            .range(..=value)
+            .next_back()
+            .is_some_and(|(_, end)| value <= *end)
+    }
+
+    pub fn insert(&mut self, interval: Interval<N>) {
+        let (mut start, mut end) = (interval.start, interval.end);
+        if let Some((prev_start, prev_end)) = self.intervals.range(..=start).next_back()
+            && touches(*prev_end, start)
+        {
+            start = *prev_start;
+            end = end.max(*prev_end);
+        }
+        while let Some((next_start, next_end)) = self.intervals.range(start..).next()
+            && touches(end, *next_start)
+        {
+            let (next_start, next_end) = (*next_start, *next_end);
+            end = end.max(next_end);
+            self.intervals.remove(&next_start);
+        }
+        self.intervals.insert(start, end);
+    }
+
+    pub fn remove(&mut self, interval: Interval<N>) {
+        let mut overlapping = self
+            .intervals
+            .range(interval.start..=interval.end)
+            .map(|(s, e)| (*s, *e))
+            .collect_vec();
+        if let Some((start, end)) = self.intervals.range(..interval.start).next_back()
+            && *end >= interval.start
+        {
+            overlapping.push((*start, *end));
+        }
+        for (start, end) in overlapping {
+            self.intervals.remove(&start);
+            if start < interval.start {
+                self.intervals.insert(start, interval.start - N::one());
+            }
+            if end > interval.end {
+                self.intervals.insert(interval.end + N::one(), end);
+            }
+        }
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+        for interval in other.iter() {
+            result.insert(interval);
+        }
+        result
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut result = Self::new();
+        let mut mine = self.iter().peekable();
+        let mut theirs = other.iter().peekable();
+        while let (Some(a), Some(b)) = (mine.peek(), theirs.peek()) {
+            if let Some(both) = a.intersection(b) {
+                result.intervals.insert(both.start, both.end);
+            }
+            if a.end < b.end {
+                mine.next();
+            } else {
+                theirs.next();
+            }
+        }
+        result
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+        for interval in other.iter() {
+            result.remove(interval);
+        }
+        result
+    }
+}
+
+/// Whether an interval ending at `end` overlaps or abuts one beginning at `start`.
+fn touches<N: NumType>(end: N, start: N) -> bool {
+    start <= end || start - end == N::one()
+}
+
+impl<N: NumType> FromIterator<Interval<N>> for IntervalSet<N> {
+    fn from_iter<T: IntoIterator<Item = Interval<N>>>(iter: T) -> Self {
+        let mut result = Self::new();
+        for interval in iter {
+            result.insert(interval);
+        }
+        result
+    }
+}
+
+impl<N: NumType + FromStr> FromStr for IntervalSet<N>
+where
+    <N as FromStr>::Err: 'static + Sync + Send + std::error::Error,
+{
+    type Err = anyhow::Error;
+
+    /// Parses intervals separated by commas or whitespace, such as `3-5,10-14`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|part| !part.is_empty())
+            .map(|part| part.parse::<Interval<N>>())
+            .collect()
+    }
+}
+
+impl<N: NumType> Display for IntervalSet<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.iter().join(","))
+    }
+}
+
+/// A piecewise translation of integers: each source interval is shifted so that its
+/// start lands on a destination start. Values outside every source map to themselves.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RangeMap<N: NumType> {
+    /// Source start to source end and destination start.
+    pieces: BTreeMap<N, (N, N)>,
+}
+
+impl<N: NumType> RangeMap<N> {
+    pub fn new() -> Self {
+        Self {
+            pieces: BTreeMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pieces.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pieces.is_empty()
+    }
+
+    /// Pairs each source interval with its destination start.
+    pub fn iter(&self) -> impl Iterator<Item = (Interval<N>, N)> + '_ {
+        self.pieces
+            .iter()
+            .map(|(start, (end, dest))| (Interval::new(*start, *end), *dest))
+    }
+
+    /// The values that are explicitly mapped.
+    pub fn domain(&self) -> IntervalSet<N> {
+        self.iter().map(|(source, _)| source).collect()
+    }
+
+    /// Maps `source` so that `source.start()` goes to `dest_start`. Fails if `source`
+    /// overlaps an interval that is already mapped.
+    pub fn insert(&mut self, source: Interval<N>, dest_start: N) -> anyhow::Result<()> {
+        // Pieces are disjoint, so only the last one starting within `source` can reach it.
+        if let Some((start, (end, _))) = self.pieces.range(..=source.end).next_back()
+            && *end >= source.start
+        {
+            bail!("{source} overlaps {}", Interval::new(*start, *end));
+        }
+        self.pieces.insert(source.start, (source.end, dest_start));
+        Ok(())
+    }
+
+    pub fn map(&self, value: N) -> N {
+        match self.pieces.range(..=value).next_back() {
+            Some((start, (end, dest))) if value <= *end => *dest + (value - *start),
+            _ => value,
+        }
+    }
+
+    /// Splits `interval` wherever it crosses a source boundary, pairing each piece
+    /// with the destination of its start.
+    fn split(&self, interval: Interval<N>) -> Vec<(Interval<N>, N)> {
+        let mut result = vec![];
+        let mut cursor = interval.start;
+        let first = self
+            .pieces
+            .range(..interval.start)
+            .next_back()
+            .filter(|(_, (end, _))| *end >= interval.start);
+        for (start, (end, dest)) in first
+            .into_iter()
+            .chain(self.pieces.range(interval.start..=interval.end))
+        {
+            if *start > cursor {
+                result.push((Interval::new(cursor, *start - N::one()), cursor));
+                cursor = *start;
+            }
+            let overlap = Interval::new(cursor, interval.end.min(*end));
+            result.push((overlap, *dest + (cursor - *start)));
+            if overlap.end == interval.end {
+                return result;
+            }
+            cursor = overlap.end + N::one();
+        }
+        result.push((Interval::new(cursor, interval.end), cursor));
+        result
+    }
+
+    pub fn map_interval(&self, interval: Interval<N>) -> IntervalSet<N> {
+        self.images(interval).collect()
+    }
+
+    pub fn map_set(&self, set: &IntervalSet<N>) -> IntervalSet<N> {
+        set.iter()
+            .flat_map(|interval| self.images(interval))
+            .collect()
+    }
+
+    fn images(&self, interval: Interval<N>) -> impl Iterator<Item = Interval<N>> {
+        self.split(interval)
+            .into_iter()
+            .map(|(piece, dest)| Interval::new(dest, dest + piece.end - piece.start))
+    }
+
+    /// The map that applies `self` and then `next`.
+    pub fn then(&self, next: &Self) -> Self {
+        let mut result = Self::new();
+        for (source, dest) in self.iter() {
+            let image = Interval::new(dest, dest + source.end - source.start);
+            for (piece, piece_dest) in next.split(image) {
+                let start = source.start + (piece.start - dest);
+                let end = source.start + (piece.end - dest);
+                result.pieces.insert(start, (end, piece_dest));
+            }
+        }
+        let domain = self.domain();
+        for (source, dest) in next.iter() {
+            let unmapped = IntervalSet::from_iter([source]).difference(&domain);
+            for gap in unmapped.iter() {
+                result
+                    .pieces
+                    .insert(gap.start, (gap.end, dest + (gap.start - source.start)));
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Interval, IntervalSet, RangeMap};
+
+    #[test]
+    fn test_insert_merges() {
+        let set = "3-5 10-14 16-20 12-18".parse::<IntervalSet<u64>>().unwrap();
+        assert_eq!(format!("{set}"), "3-5,10-20");
+        assert_eq!(set.span(), 14);
+        for (value, inside) in [
+            (1, false),
+            (3, true),
+            (5, true),
+            (8, false),
+            (11, true),
+            (17, true),
+            (32, false),
+        ] {
+            assert_eq!(set.contains(value), inside, "{value}");
+        }
+
+        let mut set = set;
+        set.insert(Interval::new(6, 9));
+        assert_eq!(format!("{set}"), "3-20");
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut set = "1-10,20-30".parse::<IntervalSet<i64>>().unwrap();
+        set.remove(Interval::new(5, 22));
+        assert_eq!(format!("{set}"), "1-4,23-30");
+        set.remove(Interval::new(24, 25));
+        assert_eq!(format!("{set}"), "1-4,23-23,26-30");
+        set.remove(Interval::new(-3, 40));
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = "1-10,20-30".parse::<IntervalSet<i64>>().unwrap();
+        let b = "-5-2,8-25".parse::<IntervalSet<i64>>().unwrap();
+        assert_eq!(format!("{}", a.intersection(&b)), "1-2,8-10,20-25");
+        assert_eq!(format!("{}", a.difference(&b)), "3-7,26-30");
+        assert_eq!(format!("{}", b.difference(&a)), "-5-0,11-19");
+        assert_eq!(format!("{}", a.union(&b)), "-5-30");
+        assert!("7-3".parse::<Interval<i64>>().is_err());
+    }
+
+    fn almanac() -> Vec<RangeMap<u64>> {
+        [
+            vec![(50, 98, 2), (52, 50, 48)],
+            vec![(0, 15, 37), (37, 52, 2), (39, 0, 15)],
+            vec![(49, 53, 8), (0, 11, 42), (42, 0, 7), (57, 7, 4)],
+            vec![(88, 18, 7), (18, 25, 70)],
+            vec![(45, 77, 23), (81, 45, 19), (68, 64, 13)],
+            vec![(0, 69, 1), (1, 0, 69)],
+            vec![(60, 56, 37), (56, 93, 4)],
+        ]
+        .iter()
+        .map(|triples| {
+            let mut map = RangeMap::new();
+            for (dest, source, len) in triples {
+                map.insert(Interval::new(*source, source + len - 1), *dest)
+                    .unwrap();
+            }
+            map
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_range_map() {
+        let maps = almanac();
+        let locations = [79, 14, 55, 13]
+            .iter()
+            .map(|seed| maps.iter().fold(*seed, |v, m| m.map(v)))
+            .collect::<Vec<_>>();
+        assert_eq!(locations, vec![82, 43, 86, 35]);
+
+        let seeds = "79-92,55-67".parse::<IntervalSet<u64>>().unwrap();
+        let located = maps.iter().fold(seeds.clone(), |set, m| m.map_set(&set));
+        assert_eq!(located.span(), seeds.span());
+        assert_eq!(located.iter().next().unwrap().start(), 46);
+
+        let mut overlapping = RangeMap::new();
+        overlapping.insert(Interval::new(5, 10), 0).unwrap();
+        assert!(overlapping.insert(Interval::new(10, 12), 0).is_err());
+        assert!(overlapping.insert(Interval::new(0, 20), 0).is_err());
+        overlapping.insert(Interval::new(0, 4), 0).unwrap();
+        overlapping.insert(Interval::new(11, 12), 0).unwrap();
+        assert_eq!(overlapping.len(), 3);
+    }
+
+    #[test]
+    fn test_compose() {
+        let maps = almanac();
+        let combined = maps[1..]
+            .iter()
+            .fold(maps[0].clone(), |m, next| m.then(next));
+        for seed in 0..120 {
+            assert_eq!(
+                combined.map(seed),
+                maps.iter().fold(seed, |v, m| m.map(v)),
+                "{seed}"
+            );
+        }
+        let seeds = "79-92,55-67".parse::<IntervalSet<u64>>().unwrap();
+        assert_eq!(
+            combined.map_set(&seeds),
+            maps.iter().fold(seeds.clone(), |set, m| m.map_set(&set))
+        );
+    }
+}
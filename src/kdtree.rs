@@ -0,0 +1,247 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, VecDeque},
+    iter::Sum,
+};
+
+use bare_metal_modulo::NumType;
+
+use crate::{multidim::Point, sub_abs};
+
+/// A k-d tree over a fixed set of points. Points are referred to by their index in
+/// the original `Vec`, and distances are squared Euclidean distances so that they
+/// stay exact.
+#[derive(Clone, Debug)]
+pub struct KdTree<N: NumType + Default + Sum, const S: usize> {
+    points: Vec<Point<N, S>>,
+    /// An implicit balanced tree: the node for `order[lo..hi]` is its middle element,
+    /// split along axis `depth % S`.
+    order: Vec<usize>,
+}
+
+impl<N: NumType + Default + Sum, const S: usize> KdTree<N, S> {
+    pub fn new(points: Vec<Point<N, S>>) -> Self {
+        let mut order = (0..points.len()).collect::<Vec<_>>();
+        Self::build(&points, &mut order, 0);
+        Self { points, order }
+    }
+
+    fn build(points: &[Point<N, S>], order: &mut [usize], depth: usize) {
+        if order.len() > 1 {
+            let axis = depth % S;
+            let mid = order.len() / 2;
+            order.select_nth_unstable_by_key(mid, |i| points[*i][axis]);
+            let (below, above) = order.split_at_mut(mid);
+            Self::build(points, below, depth + 1);
+            Self::build(points, &mut above[1..], depth + 1);
+        }
+    }
+
+    pub fn points(&self) -> &Vec<Point<N, S>> {
+        &self.points
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// The `k` points nearest to `p`, as indices paired with squared distances, closest
+    /// first. Ties are broken by index.
+    pub fn nearest(&self, p: &Point<N, S>, k: usize) -> Vec<(usize, N)> {
+        self.nearest_where(p, k, |_| true)
+    }
+
+    /// Like `nearest`, but only considers indices satisfying `include`.
+    pub fn nearest_where<F: Fn(usize) -> bool>(
+        &self,
+        p: &Point<N, S>,
+        k: usize,
+        include: F,
+    ) -> Vec<(usize, N)> {
+        let mut best = BinaryHeap::new();
+        if k > 0 {
+            self.search_nearest(p, k, &include, (0, self.order.len(), 0), &mut best);
+        }
+        let mut result = best.into_iter().map(|(d, i)| (i, d)).collect::<Vec<_>>();
+        result.sort_by_key(|(i, d)| (*d, *i));
+        result
+    }
+
+    fn search_nearest<F: Fn(usize) -> bool>(
+        &self,
+        p: &Point<N, S>,
+        k: usize,
+        include: &F,
+        (lo, hi, depth): (usize, usize, usize),
+        best: &mut BinaryHeap<(N, usize)>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        let i = self.order[mid];
+        if include(i) {
+            best.push((p.sum_squared_differences(&self.points[i]), i));
+            if best.len() > k {
+                best.pop();
+            }
+        }
+        let axis = depth % S;
+        let (near, far) = if p[axis] < self.points[i][axis] {
+            ((lo, mid), (mid + 1, hi))
+        } else {
+            ((mid + 1, hi), (lo, mid))
+        };
+        self.search_nearest(p, k, include, (near.0, near.1, depth + 1), best);
+        let gap = sub_abs(p[axis], self.points[i][axis]);
+        if best.len() < k || best.peek().is_some_and(|(worst, _)| gap * gap <= *worst) {
+            self.search_nearest(p, k, include, (far.0, far.1, depth + 1), best);
+        }
+    }
+
+    /// Every point within squared distance `max_squared` of `p`, closest first.
+    pub fn within(&self, p: &Point<N, S>, max_squared: N) -> Vec<(usize, N)> {
+        let mut result = vec![];
+        let mut pending = vec![(0, self.order.len(), 0)];
+        while let Some((lo, hi, depth)) = pending.pop() {
+            if lo < hi {
+                let mid = (lo + hi) / 2;
+                let i = self.order[mid];
+                let d = p.sum_squared_differences(&self.points[i]);
+                if d <= max_squared {
+                    result.push((i, d));
+                }
+                let axis = depth % S;
+                let gap = sub_abs(p[axis], self.points[i][axis]);
+                let crosses = gap * gap <= max_squared;
+                if p[axis] < self.points[i][axis] || crosses {
+                    pending.push((lo, mid, depth + 1));
+                }
+                if p[axis] >= self.points[i][axis] || crosses {
+                    pending.push((mid + 1, hi, depth + 1));
+                }
+            }
+        }
+        result.sort_by_key(|(i, d)| (*d, *i));
+        result
+    }
+
+    /// Lazily yields every pair of distinct points `(i, j)` with `i < j`, in order of
+    /// increasing squared distance.
+    pub fn closest_pairs(&self) -> ClosestPairs<'_, N, S> {
+        let mut result = ClosestPairs {
+            tree: self,
+            neighbors: vec![Neighbors::default(); self.len()],
+            frontier: BinaryHeap::new(),
+        };
+        for i in 0..self.len() {
+            result.advance(i);
+        }
+        result
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Neighbors<N> {
+    fetched: usize,
+    pending: VecDeque<(usize, N)>,
+}
+
+impl<N> Default for Neighbors<N> {
+    fn default() -> Self {
+        Self {
+            fetched: 0,
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+/// Each point contributes its nearest unreported higher-indexed neighbor to a
+/// frontier heap. Neighbors are fetched in batches that double in size.
+pub struct ClosestPairs<'a, N: NumType + Default + Sum, const S: usize> {
+    tree: &'a KdTree<N, S>,
+    neighbors: Vec<Neighbors<N>>,
+    frontier: BinaryHeap<Reverse<(N, usize, usize)>>,
+}
+
+impl<N: NumType + Default + Sum, const S: usize> ClosestPairs<'_, N, S> {
+    fn advance(&mut self, i: usize) {
+        let tree = self.tree;
+        let state = &mut self.neighbors[i];
+        if state.pending.is_empty() && state.fetched < tree.len() - i - 1 {
+            let k = (state.fetched * 2).max(4);
+            state.pending = tree
+                .nearest_where(&tree.points[i], k, |j| j > i)
+                .into_iter()
+                .skip(state.fetched)
+                .collect();
+            state.fetched += state.pending.len();
+        }
+        if let Some((j, d)) = state.pending.pop_front() {
+            self.frontier.push(Reverse((d, i, j)));
+        }
+    }
+}
+
+impl<N: NumType + Default + Sum, const S: usize> Iterator for ClosestPairs<'_, N, S> {
+    type Item = ((usize, usize), N);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((d, i, j)) = self.frontier.pop()?;
+        self.advance(i);
+        Some(((i, j), d))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::multidim::Point;
+
+    use super::KdTree;
+
+    fn scattered() -> Vec<Point<i64, 2>> {
+        (0..60)
+            .map(|i| Point::new([(i * 37) % 23 - 11, (i * 53) % 29 - 14]))
+            .collect()
+    }
+
+    #[test]
+    fn test_nearest_and_within() {
+        let points = scattered();
+        let tree = KdTree::new(points.clone());
+        let target = Point::new([2, -3]);
+        let mut brute = (0..points.len())
+            .map(|i| (i, points[i].sum_squared_differences(&target)))
+            .collect_vec();
+        brute.sort_by_key(|(i, d)| (*d, *i));
+        assert_eq!(tree.nearest(&target, 7), brute[..7]);
+        assert_eq!(tree.nearest(&target, 100), brute);
+        let close = brute
+            .iter()
+            .copied()
+            .filter(|(_, d)| *d <= 40)
+            .collect_vec();
+        assert_eq!(tree.within(&target, 40), close);
+    }
+
+    #[test]
+    fn test_closest_pairs() {
+        let points = scattered();
+        let tree = KdTree::new(points.clone());
+        let pairs = tree.closest_pairs().collect_vec();
+        assert_eq!(pairs.len(), points.len() * (points.len() - 1) / 2);
+        assert!(pairs.windows(2).all(|w| w[0].1 <= w[1].1));
+        assert!(
+            pairs.iter().all(|((i, j), d)| {
+                i < j && points[*i].sum_squared_differences(&points[*j]) == *d
+            })
+        );
+        assert_eq!(pairs.iter().map(|(p, _)| *p).unique().count(), pairs.len());
+    }
+}
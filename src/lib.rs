@@ -1,10 +1,24 @@
+pub mod aabox;
+pub mod automaton;
+pub mod beam;
 pub mod combinations;
+pub mod compress;
 pub mod extended_euclid;
+pub mod geometry;
+pub mod gf2;
 pub mod graph;
 pub mod grid;
+pub mod ilp;
+pub mod interval;
+pub mod kdtree;
+pub mod linear_system;
+pub mod mst;
 pub mod multidim;
+pub mod pathfinding;
+pub mod render;
 pub mod search_iter;
 pub mod union_find;
+pub mod viewer;
 
 use std::{
     env,
@@ -0,0 +1,253 @@
+use num::{Integer, Signed, Zero, rational::Ratio};
+
+/// A matrix of exact rationals, such as `Ratio<i64>` or `BigRational`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RationalMatrix<T: Clone + Integer + Signed> {
+    rows: Vec<Vec<Ratio<T>>>,
+    num_cols: usize,
+}
+
+impl<T: Clone + Integer + Signed> RationalMatrix<T> {
+    /// Every row must have `num_cols` entries.
+    pub fn new(rows: Vec<Vec<Ratio<T>>>, num_cols: usize) -> Self {
+        assert!(rows.iter().all(|row| row.len() == num_cols));
+        Self { rows, num_cols }
+    }
+
+    pub fn from_integers(rows: Vec<Vec<T>>, num_cols: usize) -> Self {
+        Self::new(
+            rows.into_iter()
+                .map(|row| row.into_iter().map(Ratio::from_integer).collect())
+                .collect(),
+            num_cols,
+        )
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn num_cols(&self) -> usize {
+        self.num_cols
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> &Ratio<T> {
+        &self.rows[row][col]
+    }
+
+    /// Gauss-Jordan elimination. Returns the reduced row echelon form and the column
+    /// of each pivot, in row order.
+    pub fn reduced_row_echelon(&self) -> (Self, Vec<usize>) {
+        let mut rows = self.rows.clone();
+        let mut pivots = vec![];
+        for col in 0..self.num_cols {
+            let row = pivots.len();
+            let Some(found) = (row..rows.len()).find(|r| !rows[*r][col].is_zero()) else {
+                continue;
+            };
+            rows.swap(row, found);
+            let pivot = rows[row][col].clone();
+            for entry in rows[row].iter_mut() {
+                *entry = entry.clone() / pivot.clone();
+            }
+            let pivot_row = rows[row].clone();
+            for (other, other_row) in rows.iter_mut().enumerate() {
+                if other != row && !other_row[col].is_zero() {
+                    let factor = other_row[col].clone();
+                    for (entry, p) in other_row.iter_mut().zip(pivot_row.iter()) {
+                        *entry = entry.clone() - factor.clone() * p.clone();
+                    }
+                }
+            }
+            pivots.push(col);
+        }
+        (
+            Self {
+                rows,
+                num_cols: self.num_cols,
+            },
+            pivots,
+        )
+    }
+
+    pub fn rank(&self) -> usize {
+        self.reduced_row_echelon().1.len()
+    }
+
+    /// Solves `self * x = rhs`, returning `None` if the system is inconsistent.
+    pub fn solve(&self, rhs: &[Ratio<T>]) -> Option<SolutionSpace<T>> {
+        assert_eq!(rhs.len(), self.num_rows());
+        let augmented = Self::new(
+            self.rows
+                .iter()
+                .zip(rhs.iter())
+                .map(|(row, b)| {
+                    let mut row = row.clone();
+                    row.push(b.clone());
+                    row
+                })
+                .collect(),
+            self.num_cols + 1,
+        );
+        let (reduced, pivots) = augmented.reduced_row_echelon();
+        if pivots.last() == Some(&self.num_cols) {
+            return None;
+        }
+
+        let free = (0..self.num_cols)
+            .filter(|c| !pivots.contains(c))
+            .collect::<Vec<_>>();
+        let mut particular = vec![Ratio::zero(); self.num_cols];
+        for (row, col) in pivots.iter().enumerate() {
+            particular[*col] = reduced.rows[row][self.num_cols].clone();
+        }
+        let null_space = free
+            .iter()
+            .map(|f| {
+                let mut basis = vec![Ratio::zero(); self.num_cols];
+                basis[*f] = Ratio::from_integer(T::one());
+                for (row, col) in pivots.iter().enumerate() {
+                    basis[*col] = -reduced.rows[row][*f].clone();
+                }
+                basis
+            })
+            .collect();
+        Some(SolutionSpace {
+            particular,
+            null_space,
+            pivots,
+            free,
+        })
+    }
+}
+
+/// Every solution of a consistent linear system: the particular solution plus any
+/// combination of the null-space basis vectors.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SolutionSpace<T: Clone + Integer + Signed> {
+    particular: Vec<Ratio<T>>,
+    null_space: Vec<Vec<Ratio<T>>>,
+    pivots: Vec<usize>,
+    free: Vec<usize>,
+}
+
+impl<T: Clone + Integer + Signed> SolutionSpace<T> {
+    /// The solution with every free variable set to zero.
+    pub fn particular(&self) -> &Vec<Ratio<T>> {
+        &self.particular
+    }
+
+    /// One basis vector per free variable, with that variable set to one and the
+    /// other free variables set to zero.
+    pub fn null_space(&self) -> &Vec<Vec<Ratio<T>>> {
+        &self.null_space
+    }
+
+    pub fn rank(&self) -> usize {
+        self.pivots.len()
+    }
+
+    /// Columns holding a pivot, whose values follow from the free variables.
+    pub fn pivot_vars(&self) -> &Vec<usize> {
+        &self.pivots
+    }
+
+    pub fn free_vars(&self) -> &Vec<usize> {
+        &self.free
+    }
+
+    pub fn is_unique(&self) -> bool {
+        self.free.is_empty()
+    }
+
+    /// The solution whose free variables take the given values, in the order of
+    /// `free_vars()`.
+    pub fn solution(&self, free_values: &[Ratio<T>]) -> Vec<Ratio<T>> {
+        assert_eq!(free_values.len(), self.free.len());
+        let mut result = self.particular.clone();
+        for (basis, value) in self.null_space.iter().zip(free_values.iter()) {
+            for (entry, b) in result.iter_mut().zip(basis.iter()) {
+                *entry = entry.clone() + b.clone() * value.clone();
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::{BigInt, BigRational, rational::Ratio};
+
+    use super::RationalMatrix;
+
+    fn ratios(values: &[i64]) -> Vec<Ratio<i64>> {
+        values.iter().map(|v| Ratio::from_integer(*v)).collect()
+    }
+
+    #[test]
+    fn test_unique() {
+        let m =
+            RationalMatrix::from_integers(vec![vec![2, 1, -1], vec![-3, -1, 2], vec![-2, 1, 2]], 3);
+        assert_eq!(m.rank(), 3);
+        let space = m.solve(&ratios(&[8, -11, -3])).unwrap();
+        assert!(space.is_unique());
+        assert_eq!(space.particular(), &ratios(&[2, 3, -1]));
+    }
+
+    #[test]
+    fn test_underdetermined() {
+        // The lights of the first day10 example machine, as counters.
+        let buttons: Vec<Vec<usize>> = vec![
+            vec![3],
+            vec![1, 3],
+            vec![2],
+            vec![2, 3],
+            vec![0, 2],
+            vec![0, 1],
+        ];
+        let rows = (0..4)
+            .map(|light| {
+                buttons
+                    .iter()
+                    .map(|b| if b.contains(&light) { 1 } else { 0 })
+                    .collect()
+            })
+            .collect();
+        let m = RationalMatrix::from_integers(rows, 6);
+        let target = ratios(&[3, 5, 4, 7]);
+        let space = m.solve(&target).unwrap();
+        assert_eq!(space.rank(), 4);
+        assert_eq!(space.free_vars().len(), 2);
+        for free in [ratios(&[0, 0]), ratios(&[1, 2]), ratios(&[-3, 5])] {
+            let x = space.solution(&free);
+            for (light, expected) in target.iter().enumerate() {
+                let total = x
+                    .iter()
+                    .enumerate()
+                    .map(|(b, xb)| m.get(light, b) * xb)
+                    .sum::<Ratio<i64>>();
+                assert_eq!(total, *expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_inconsistent_and_big() {
+        let m = RationalMatrix::from_integers(vec![vec![1, 1], vec![2, 2]], 2);
+        assert_eq!(m.rank(), 1);
+        assert!(m.solve(&ratios(&[1, 3])).is_none());
+
+        let big = RationalMatrix::<BigInt>::from_integers(
+            vec![vec![BigInt::from(3), BigInt::from(4)]],
+            2,
+        );
+        let space = big
+            .solve(&[BigRational::from_integer(BigInt::from(7))])
+            .unwrap();
+        assert_eq!(space.null_space().len(), 1);
+        assert_eq!(
+            space.particular()[0],
+            BigRational::new(BigInt::from(7), BigInt::from(3))
+        );
+    }
+}
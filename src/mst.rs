@@ -0,0 +1,189 @@
+use std::{cmp::Ordering, iter::Sum};
+
+use crate::union_find::{DisjointSetForest, DisjointSetKey};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Edge<V, W> {
+    pub a: V,
+    pub b: V,
+    pub weight: W,
+}
+
+impl<V, W> Edge<V, W> {
+    pub fn new(a: V, b: V, weight: W) -> Self {
+        Self { a, b, weight }
+    }
+}
+
+/// An edge that joined two components, along with how many components remained.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct MergeEvent<V, W> {
+    pub edge: Edge<V, W>,
+    pub components: usize,
+}
+
+/// Kruskal's algorithm as an iterator. Edges must arrive in nondecreasing weight
+/// order; they are consumed lazily, and iteration ends once every vertex is
+/// connected or the edges run out. Edges touching a vertex that was not supplied
+/// up front are skipped.
+pub struct MergeEvents<V: DisjointSetKey, W, I: Iterator<Item = Edge<V, W>>> {
+    forest: DisjointSetForest<V>,
+    edges: I,
+}
+
+impl<V: DisjointSetKey, W, I: Iterator<Item = Edge<V, W>>> MergeEvents<V, W, I> {
+    pub fn new<VI: IntoIterator<Item = V>, EI: IntoIterator<IntoIter = I>>(
+        vertices: VI,
+        sorted_edges: EI,
+    ) -> Self {
+        let mut forest = DisjointSetForest::default();
+        for v in vertices {
+            forest.make_set(v);
+        }
+        Self {
+            forest,
+            edges: sorted_edges.into_iter(),
+        }
+    }
+
+    pub fn components(&self) -> usize {
+        self.forest.num_roots()
+    }
+}
+
+impl<V: DisjointSetKey, W, I: Iterator<Item = Edge<V, W>>> Iterator for MergeEvents<V, W, I> {
+    type Item = MergeEvent<V, W>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.forest.num_roots() > 1 {
+            let edge = self.edges.next()?;
+            let known = self.forest.find(&edge.a).is_some() && self.forest.find(&edge.b).is_some();
+            if known && self.forest.union(&edge.a, &edge.b) {
+                return Some(MergeEvent {
+                    edge,
+                    components: self.forest.num_roots(),
+                });
+            }
+        }
+        None
+    }
+}
+
+/// A minimum spanning forest: a tree per connected component.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SpanningTree<V, W> {
+    edges: Vec<Edge<V, W>>,
+    components: usize,
+}
+
+impl<V: DisjointSetKey, W: Copy + PartialOrd + Sum> SpanningTree<V, W> {
+    /// Builds the tree with Kruskal's algorithm. Weights may be floats, but must not
+    /// be NaN.
+    pub fn kruskal<VI: IntoIterator<Item = V>>(vertices: VI, mut edges: Vec<Edge<V, W>>) -> Self {
+        edges.sort_by(|e1, e2| Self::compare(e1.weight, e2.weight));
+        let mut merges = MergeEvents::new(vertices, edges);
+        let edges = merges.by_ref().map(|m| m.edge).collect();
+        Self {
+            edges,
+            components: merges.components(),
+        }
+    }
+
+    fn compare(w1: W, w2: W) -> Ordering {
+        w1.partial_cmp(&w2)
+            .expect("edge weights must be comparable")
+    }
+
+    /// Edges in the order they were added, which is nondecreasing by weight.
+    pub fn edges(&self) -> &Vec<Edge<V, W>> {
+        &self.edges
+    }
+
+    pub fn total_weight(&self) -> W {
+        self.edges.iter().map(|e| e.weight).sum()
+    }
+
+    /// The edge whose addition completed the tree.
+    pub fn last_edge(&self) -> Option<&Edge<V, W>> {
+        self.edges.last()
+    }
+
+    /// The number of connected components; 1 when the graph is connected.
+    pub fn components(&self) -> usize {
+        self.components
+    }
+}
+
+impl<W: Copy + PartialOrd + Sum> SpanningTree<usize, W> {
+    /// Builds the tree over the complete graph on `points`, weighting each pair by
+    /// `metric`. Vertices are indices into `points`.
+    pub fn from_points<P, M: Fn(&P, &P) -> W>(points: &[P], metric: M) -> Self {
+        let mut edges = vec![];
+        for i in 0..points.len() {
+            for j in i + 1..points.len() {
+                edges.push(Edge::new(i, j, metric(&points[i], &points[j])));
+            }
+        }
+        Self::kruskal(0..points.len(), edges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::multidim::Point;
+
+    use super::{Edge, MergeEvents, SpanningTree};
+
+    #[test]
+    fn test_kruskal() {
+        let edges = [
+            ('a', 'b', 7),
+            ('a', 'd', 5),
+            ('b', 'c', 8),
+            ('b', 'd', 9),
+            ('b', 'e', 7),
+            ('c', 'e', 5),
+            ('d', 'e', 15),
+            ('d', 'f', 6),
+            ('e', 'f', 8),
+            ('e', 'g', 9),
+            ('f', 'g', 11),
+        ]
+        .iter()
+        .map(|(a, b, w)| Edge::new(*a, *b, *w))
+        .collect::<Vec<_>>();
+        let tree = SpanningTree::kruskal('a'..='g', edges);
+        assert_eq!(tree.components(), 1);
+        assert_eq!(tree.edges().len(), 6);
+        assert_eq!(tree.total_weight(), 39);
+        assert_eq!(tree.last_edge(), Some(&Edge::new('e', 'g', 9)));
+
+        let forest = SpanningTree::kruskal('a'..='c', vec![Edge::new('a', 'b', 1)]);
+        assert_eq!(forest.components(), 2);
+    }
+
+    #[test]
+    fn test_points() {
+        let points = [(0, 0), (0, 3), (4, 0), (4, 4), (10, 10)].map(|(x, y)| Point::new([x, y]));
+        let tree = SpanningTree::from_points(&points, |p, q| p.manhattan_distance(q));
+        assert_eq!(tree.total_weight(), 3 + 4 + 4 + 12);
+        assert_eq!(tree.last_edge(), Some(&Edge::new(3, 4, 12)));
+
+        let events = MergeEvents::new(
+            0..3,
+            [Edge::new(0, 1, 1), Edge::new(1, 0, 2), Edge::new(2, 1, 3)],
+        )
+        .map(|m| m.components)
+        .collect::<Vec<_>>();
+        assert_eq!(events, vec![2, 1]);
+
+        let unknown = MergeEvents::new(0..2, [Edge::new(0, 5, 1), Edge::new(0, 1, 2)])
+            .map(|m| m.edge)
+            .collect::<Vec<_>>();
+        assert_eq!(unknown, vec![Edge::new(0, 1, 2)]);
+
+        let euclidean = SpanningTree::from_points(&points, |p, q| p.euclidean_distance(q));
+        assert_eq!(euclidean.edges().len(), 4);
+        assert!((euclidean.total_weight() - (3.0 + 4.0 + 4.0 + 72f64.sqrt())).abs() < 1e-9);
+    }
+}
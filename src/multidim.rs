@@ -13,7 +13,7 @@ use bare_metal_modulo::NumType;
 use enum_iterator::{Sequence, all};
 use num::ToPrimitive;
 
-use crate::{all_lines, sub_abs};
+use crate::{all_lines, combinations::ComboIterator, sub_abs};
 
 pub type Position = Point<isize, 2>;
 
@@ -31,6 +31,10 @@ impl Position {
         Self::from_isize((x as isize, y as isize))
     }
 
+    pub fn line_to(&self, end: Position) -> LineIter {
+        LineIter::new(*self, end)
+    }
+
     pub fn next_in_grid(&self, width: usize, height: usize) -> Option<Position> {
         let mut result = self.clone();
         result[0] += 1;
@@ -150,6 +154,15 @@ impl<N: NumType + num::traits::Signed + Sum<N> + Default, const S: usize> Point<
         result
     }
 
+    /// All 3^S - 1 points differing from this one by at most one in every coordinate.
+    pub fn all_neighbors(&self) -> Vec<Point<N, S>> {
+        ComboIterator::new([-N::one(), N::zero(), N::one()].into_iter(), S)
+            .map(|offset| Self::from_iter(offset.into_iter()))
+            .filter(|offset| *offset != Self::default())
+            .map(|offset| *self + offset)
+            .collect()
+    }
+
     pub fn adjacent(&self, other: &Point<N, S>) -> bool {
         self.manhattan_distance(other) == N::one()
     }
@@ -528,6 +541,62 @@ pub fn normalize_degrees(degrees: isize) -> isize {
     degrees % 360
 }
 
+/// Cells along the Bresenham line between two positions, including both endpoints.
+pub struct LineIter {
+    current: Position,
+    end: Position,
+    dx: isize,
+    dy: isize,
+    step: Position,
+    error: isize,
+    done: bool,
+}
+
+impl LineIter {
+    pub fn new(start: Position, end: Position) -> Self {
+        let dx = (end[0] - start[0]).abs();
+        let dy = -(end[1] - start[1]).abs();
+        Self {
+            current: start,
+            end,
+            dx,
+            dy,
+            step: Position::from_isize((
+                (end[0] - start[0]).signum(),
+                (end[1] - start[1]).signum(),
+            )),
+            error: dx + dy,
+            done: false,
+        }
+    }
+}
+
+impl Iterator for LineIter {
+    type Item = Position;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            None
+        } else {
+            let result = self.current;
+            if self.current == self.end {
+                self.done = true;
+            } else {
+                let doubled = 2 * self.error;
+                if doubled >= self.dy {
+                    self.error += self.dy;
+                    self.current[0] += self.step[0];
+                }
+                if doubled <= self.dx {
+                    self.error += self.dx;
+                    self.current[1] += self.step[1];
+                }
+            }
+            Some(result)
+        }
+    }
+}
+
 pub struct RowMajorPositionIterator {
     width: usize,
     height: usize,
@@ -632,7 +701,7 @@ pub fn map_width_height<V>(map: &HashMap<Position, V>) -> (usize, usize) {
 
 #[cfg(test)]
 mod tests {
-    use super::{Dir, DirType, Position};
+    use super::{Dir, DirType, Point, Position};
     use enum_iterator::all;
 
     #[test]
@@ -677,6 +746,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_line_iter() {
+        for (start, end, expected) in [
+            ((0, 0), (3, 0), vec![(0, 0), (1, 0), (2, 0), (3, 0)]),
+            ((2, 2), (2, 2), vec![(2, 2)]),
+            ((0, 0), (-2, -2), vec![(0, 0), (-1, -1), (-2, -2)]),
+            (
+                (0, 0),
+                (5, 2),
+                vec![(0, 0), (1, 0), (2, 1), (3, 1), (4, 2), (5, 2)],
+            ),
+            ((1, 0), (0, 3), vec![(1, 0), (1, 1), (0, 2), (0, 3)]),
+        ] {
+            let line = Position::from_isize(start)
+                .line_to(Position::from_isize(end))
+                .map(|p| (p[0], p[1]))
+                .collect::<Vec<_>>();
+            assert_eq!(line, expected);
+        }
+    }
+
+    #[test]
+    fn test_all_neighbors() {
+        let p = Point::<isize, 3>::new([1, 2, 3]);
+        let neighbors = p.all_neighbors();
+        assert_eq!(neighbors.len(), 26);
+        assert!(!neighbors.contains(&p));
+        assert!(
+            neighbors
+                .iter()
+                .all(|n| (0..3).all(|i| (n[i] - p[i]).abs() <= 1))
+        );
+        assert_eq!(Position::default().all_neighbors().len(), 8);
+    }
+
     #[test]
     fn test_manhattan_distance() {
         for ((x1, y1), (x2, y2), d) in [
@@ -0,0 +1,222 @@
+use crate::{
+    grid::GridWorld,
+    multidim::{DirType, ManhattanDir, Position},
+    search_iter::{Estimator, PrioritySearchIter},
+};
+
+/// A search state for mazes where facing matters: where we are, which way we face,
+/// and how many cells we have moved in a row in that direction. Without a momentum
+/// limit the run length is capped once it no longer matters, so that each cell is
+/// expanded only a few times per direction.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Heading {
+    pub p: Position,
+    pub dir: ManhattanDir,
+    pub straight: usize,
+}
+
+impl Heading {
+    pub fn new(p: Position, dir: ManhattanDir) -> Self {
+        Self {
+            p,
+            dir,
+            straight: 0,
+        }
+    }
+}
+
+/// Costs for moving through a grid. By default, turning rotates 90 degrees and moves
+/// into the neighboring cell in the new direction, costing `turn + forward`, and
+/// reversing is not allowed. With `with_rotation_in_place`, turning instead rotates
+/// without moving and costs `turn`, so reversing takes two turns.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct TurnCosts<N: Estimator> {
+    pub forward: N,
+    pub turn: N,
+    pub min_straight: usize,
+    pub max_straight: usize,
+    pub rotate_in_place: bool,
+}
+
+impl<N: Estimator> TurnCosts<N> {
+    pub fn new(forward: N, turn: N) -> Self {
+        Self {
+            forward,
+            turn,
+            min_straight: 0,
+            max_straight: usize::MAX,
+            rotate_in_place: false,
+        }
+    }
+
+    /// Requires at least `min_straight` and at most `max_straight` consecutive moves
+    /// in one direction before turning. The goal only counts once `min_straight` is met.
+    pub fn with_momentum(self, min_straight: usize, max_straight: usize) -> Self {
+        Self {
+            min_straight,
+            max_straight,
+            ..self
+        }
+    }
+
+    pub fn with_rotation_in_place(self) -> Self {
+        Self {
+            rotate_in_place: true,
+            ..self
+        }
+    }
+
+    /// The run length after one more forward move. Without an upper limit, only
+    /// whether `min_straight` has been reached matters.
+    fn next_straight(&self, straight: usize) -> usize {
+        if self.max_straight == usize::MAX {
+            (straight + 1).min(self.min_straight.max(1))
+        } else {
+            straight + 1
+        }
+    }
+
+    pub fn successors<P: Fn(Position) -> bool>(
+        &self,
+        h: &Heading,
+        passable: P,
+    ) -> Vec<(Heading, N)> {
+        let mut result = vec![];
+        if h.straight < self.max_straight {
+            result.push((
+                Heading {
+                    p: h.dir.neighbor(h.p),
+                    dir: h.dir,
+                    straight: self.next_straight(h.straight),
+                },
+                self.forward,
+            ));
+        }
+        if h.straight == 0 || h.straight >= self.min_straight {
+            for dir in [h.dir.clockwise(), h.dir.counterclockwise()] {
+                result.push(if self.rotate_in_place {
+                    (Heading::new(h.p, dir), self.turn)
+                } else {
+                    (
+                        Heading {
+                            p: dir.neighbor(h.p),
+                            dir,
+                            straight: self.next_straight(0),
+                        },
+                        self.turn + self.forward,
+                    )
+                });
+            }
+        }
+        result.retain(|(next, _)| passable(next.p));
+        result
+    }
+}
+
+impl<V: Copy + Clone + Eq + PartialEq> GridWorld<V> {
+    /// Finds the cheapest route from `start` to `goal` through in-bounds cells whose
+    /// values satisfy `passable`. Returns the headings along the route, starting with
+    /// `start`, and the total cost.
+    pub fn turn_cost_path<N: Estimator, P: Fn(V) -> bool>(
+        &self,
+        start: Heading,
+        goal: Position,
+        costs: &TurnCosts<N>,
+        passable: P,
+    ) -> Option<(Vec<Heading>, N)> {
+        let mut searcher = PrioritySearchIter::dijkstra(start, |h| {
+            costs.successors(h, |p| self.value(p).is_some_and(&passable))
+        });
+        let end = searcher
+            .by_ref()
+            .find(|h| h.p == goal && h.straight >= costs.min_straight)?;
+        let mut path = searcher.path_back_from(&end);
+        path.make_contiguous().reverse();
+        Some((path.into_iter().collect(), searcher.cost_for(&end)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        grid::GridCharWorld,
+        multidim::{ManhattanDir, Position},
+    };
+
+    use super::{Heading, TurnCosts};
+
+    #[test]
+    fn test_reindeer_maze() {
+        let maze = "###############
+#.......#....E#
+#.#.###.#.###.#
+#.....#.#...#.#
+#.###.#####.#.#
+#.#.#.......#.#
+#.#.#####.###.#
+#...........#.#
+###.#.#####.#.#
+#...#.....#.#.#
+#.#.#.###.#.#.#
+#.....#...#.#.#
+#.###.#.#.#.#.#
+#S..#.....#...#
+###############"
+            .parse::<GridCharWorld>()
+            .unwrap();
+        let start = Heading::new(maze.any_position_for('S'), ManhattanDir::E);
+        let goal = maze.any_position_for('E');
+        let (path, cost) = maze
+            .turn_cost_path(start, goal, &TurnCosts::new(1, 1000), |c| c != '#')
+            .unwrap();
+        assert_eq!(cost, 7036);
+        assert_eq!(path[0], start);
+        assert_eq!(path[path.len() - 1].p, goal);
+
+        let rotating = TurnCosts::new(1, 1000).with_rotation_in_place();
+        let (_, cost) = maze
+            .turn_cost_path(start, goal, &rotating, |c| c != '#')
+            .unwrap();
+        assert_eq!(cost, 7036);
+    }
+
+    #[test]
+    fn test_turn_around() {
+        let corridor = "E..S".parse::<GridCharWorld>().unwrap();
+        let start = Heading::new(corridor.any_position_for('S'), ManhattanDir::E);
+        let goal = corridor.any_position_for('E');
+        let moving = TurnCosts::new(1, 1000);
+        assert_eq!(
+            corridor.turn_cost_path(start, goal, &moving, |_| true),
+            None
+        );
+        let (path, cost) = corridor
+            .turn_cost_path(start, goal, &moving.with_rotation_in_place(), |_| true)
+            .unwrap();
+        assert_eq!(cost, 2003);
+        assert!(path.iter().all(|h| h.straight <= 1));
+    }
+
+    #[test]
+    fn test_momentum() {
+        let corridor = ".....".parse::<GridCharWorld>().unwrap();
+        let start = Heading::new(Position::default(), ManhattanDir::E);
+        let goal = Position::from_usize(4, 0);
+        let limited = TurnCosts::new(1, 0).with_momentum(0, 3);
+        assert_eq!(
+            corridor.turn_cost_path(start, goal, &limited, |_| true),
+            None
+        );
+
+        let open = ".....\n.....\n.....".parse::<GridCharWorld>().unwrap();
+        let (path, cost) = open
+            .turn_cost_path(start, goal, &limited, |_| true)
+            .unwrap();
+        assert_eq!(cost, 6);
+        assert!(path.iter().all(|h| h.straight <= 3));
+
+        let needs_run = TurnCosts::new(1, 0).with_momentum(4, 10);
+        let goal = Position::from_usize(2, 0);
+        assert_eq!(open.turn_cost_path(start, goal, &needs_run, |_| true), None);
+    }
+}
@@ -0,0 +1,145 @@
+use std::{collections::BTreeSet, fs};
+
+use crate::{
+    grid::{CharDisplay, GridWorld},
+    multidim::Position,
+};
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb::new(0, 0, 0);
+    pub const WHITE: Rgb = Rgb::new(255, 255, 255);
+    pub const RED: Rgb = Rgb::new(255, 0, 0);
+    pub const GREEN: Rgb = Rgb::new(0, 255, 0);
+    pub const BLUE: Rgb = Rgb::new(0, 0, 255);
+    pub const YELLOW: Rgb = Rgb::new(255, 255, 0);
+    pub const GRAY: Rgb = Rgb::new(128, 128, 128);
+
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    fn ansi_foreground(&self) -> String {
+        format!("\x1b[38;2;{};{};{}m", self.r, self.g, self.b)
+    }
+}
+
+const ANSI_REVERSE: &str = "\x1b[7m";
+const ANSI_RESET: &str = "\x1b[0m";
+
+impl<V: Copy + Clone + Eq + PartialEq> GridWorld<V> {
+    /// Row-major RGB bytes, three per cell.
+    pub fn to_rgb_bytes<C: Fn(V) -> Rgb>(&self, color: C) -> Vec<u8> {
+        self.position_value_iter()
+            .flat_map(|(_, v)| {
+                let c = color(v);
+                [c.r, c.g, c.b]
+            })
+            .collect()
+    }
+
+    pub fn to_ppm<C: Fn(V) -> Rgb>(&self, color: C) -> Vec<u8> {
+        let mut result = format!("P6\n{} {}\n255\n", self.width(), self.height()).into_bytes();
+        result.append(&mut self.to_rgb_bytes(color));
+        result
+    }
+
+    pub fn write_ppm<C: Fn(V) -> Rgb>(&self, filename: &str, color: C) -> anyhow::Result<()> {
+        fs::write(filename, self.to_ppm(color))?;
+        Ok(())
+    }
+
+    #[cfg(feature = "png")]
+    pub fn write_png<C: Fn(V) -> Rgb>(&self, filename: &str, color: C) -> anyhow::Result<()> {
+        let file = std::io::BufWriter::new(fs::File::create(filename)?);
+        let mut encoder = png::Encoder::new(file, self.width() as u32, self.height() as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.to_rgb_bytes(color))?;
+        writer.finish()?;
+        Ok(())
+    }
+}
+
+impl<V: CharDisplay + Copy + Clone + Eq + PartialEq> GridWorld<V> {
+    /// Renders each cell's character in its mapped color, drawing `highlights`
+    /// (such as a path from `path_back_from()`) in reverse video.
+    pub fn ansi_string<C: Fn(V) -> Rgb, I: IntoIterator<Item = Position>>(
+        &self,
+        color: C,
+        highlights: I,
+    ) -> String {
+        let highlights = highlights.into_iter().collect::<BTreeSet<_>>();
+        let mut result = String::new();
+        for (p, v) in self.position_value_iter() {
+            if p[1] > 0 && p[0] == 0 {
+                result.push_str(ANSI_RESET);
+                result.push('\n');
+            }
+            result.push_str(color(v).ansi_foreground().as_str());
+            if highlights.contains(&p) {
+                result.push_str(ANSI_REVERSE);
+                result.push(v.display());
+                result.push_str(ANSI_RESET);
+            } else {
+                result.push(v.display());
+            }
+        }
+        result.push_str(ANSI_RESET);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{grid::GridCharWorld, multidim::Position};
+
+    use super::Rgb;
+
+    fn wall_color(c: char) -> Rgb {
+        if c == '#' { Rgb::BLACK } else { Rgb::WHITE }
+    }
+
+    #[test]
+    fn test_ppm() {
+        let world = "#.\n.#".parse::<GridCharWorld>().unwrap();
+        let ppm = world.to_ppm(wall_color);
+        let header = b"P6\n2 2\n255\n";
+        assert_eq!(&ppm[..header.len()], header);
+        assert_eq!(
+            &ppm[header.len()..],
+            &[0, 0, 0, 255, 255, 255, 255, 255, 255, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_ansi() {
+        let world = "#.\n.#".parse::<GridCharWorld>().unwrap();
+        let plain = world.ansi_string(wall_color, []);
+        assert!(!plain.contains("\x1b[7m"));
+        assert_eq!(plain.matches("\x1b[38;2;0;0;0m#").count(), 2);
+
+        let highlighted = world.ansi_string(wall_color, [Position::from_usize(1, 0)]);
+        assert!(highlighted.contains("\x1b[38;2;255;255;255m\x1b[7m.\x1b[0m"));
+        assert_eq!(highlighted.lines().count(), 2);
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn test_png() {
+        let world = "#.\n.#".parse::<GridCharWorld>().unwrap();
+        let filename = std::env::temp_dir().join("advent2025_render_test.png");
+        let filename = filename.to_str().unwrap();
+        world.write_png(filename, wall_color).unwrap();
+        let bytes = std::fs::read(filename).unwrap();
+        assert_eq!(&bytes[1..4], b"PNG");
+        std::fs::remove_file(filename).unwrap();
+    }
+}
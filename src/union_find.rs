@@ -1,6 +1,9 @@
+use anyhow::bail;
+use bare_metal_modulo::NumType;
+use num::Signed;
 use std::fmt::Debug;
 use std::{
-    collections::{BTreeSet, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap},
     hash::Hash,
 };
 use trait_set::trait_set;
@@ -9,84 +12,379 @@ trait_set! {
     pub trait DisjointSetKey = Copy + Hash + Eq + Debug;
 }
 
-#[derive(Default)]
-pub struct DisjointSetForest<V: DisjointSetKey> {
-    nodes: Vec<Node>,
+/// Nodes, value lookup and root tracking shared by the forests in this module. Each
+/// node carries extra data `D`, such as an offset to its parent.
+struct Core<V: DisjointSetKey, D> {
+    nodes: Vec<Node<V, D>>,
     value2index: HashMap<V, usize>,
     roots: BTreeSet<usize>,
 }
 
+struct Node<V, D> {
+    parent: usize,
+    size: usize,
+    value: V,
+    data: D,
+}
+
+impl<V: DisjointSetKey, D> Default for Core<V, D> {
+    fn default() -> Self {
+        Self {
+            nodes: vec![],
+            value2index: HashMap::new(),
+            roots: BTreeSet::new(),
+        }
+    }
+}
+
+impl<V: DisjointSetKey, D> Core<V, D> {
+    /// The index of `value`, adding it as a singleton set with `data` if it is new.
+    /// Also returns whether it was added.
+    fn insert(&mut self, value: V, data: D) -> (usize, bool) {
+        if let Some(index) = self.value2index.get(&value) {
+            return (*index, false);
+        }
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            parent: index,
+            size: 1,
+            value,
+            data,
+        });
+        self.value2index.insert(value, index);
+        self.roots.insert(index);
+        (index, true)
+    }
+
+    fn index(&self, v: &V) -> Option<usize> {
+        self.value2index.get(v).copied()
+    }
+
+    /// Links two distinct roots by size, returning `(child, parent)`.
+    fn link(&mut self, root1: usize, root2: usize) -> Option<(usize, usize)> {
+        if root1 == root2 {
+            return None;
+        }
+        let (child, parent) = if self.nodes[root1].size > self.nodes[root2].size {
+            (root2, root1)
+        } else {
+            (root1, root2)
+        };
+        self.nodes[child].parent = parent;
+        self.nodes[parent].size += self.nodes[child].size;
+        self.roots.remove(&child);
+        Some((child, parent))
+    }
+
+    fn all_sizes(&self) -> impl Iterator<Item = usize> {
+        self.roots.iter().map(|root| self.nodes[*root].size)
+    }
+}
+
+pub struct DisjointSetForest<V: DisjointSetKey> {
+    core: Core<V, ()>,
+}
+
+impl<V: DisjointSetKey> Default for DisjointSetForest<V> {
+    fn default() -> Self {
+        Self {
+            core: Core::default(),
+        }
+    }
+}
+
 impl<V: DisjointSetKey> DisjointSetForest<V> {
+    /// Adds `value` as a singleton set; does nothing if it is already present.
     pub fn make_set(&mut self, value: V) {
-        let parent = self.nodes.len();
-        self.nodes.push(Node { parent, size: 1 });
-        self.value2index.insert(value, parent);
-        self.roots.insert(parent);
+        self.core.insert(value, ());
     }
 
     pub fn num_roots(&self) -> usize {
-        self.roots.len()
-    }
-
-    fn index_of(&mut self, v: &V) -> usize {
-        if !self.value2index.contains_key(v) {
-            self.make_set(*v);
-        }
-        self.value2index.get(v).copied().unwrap()
-    }
-
-    pub fn union(&mut self, v1: &V, v2: &V) {
-        let index1 = self.index_of(v1);
-        let f1 = self.find(index1);
-        let index2 = self.index_of(v2);
-        let f2 = self.find(index2);
-        if f1 != f2 {
-            if self.nodes[f1].size > self.nodes[f2].size {
-                self.nodes[f2].parent = f1;
-                self.nodes[f1].size += self.nodes[f2].size;
-                self.roots.remove(&f2);
-            } else {
-                self.nodes[f1].parent = f2;
-                self.nodes[f2].size += self.nodes[f1].size;
-                self.roots.remove(&f1);
-            }
+        self.core.roots.len()
+    }
+
+    /// Merges the sets containing `v1` and `v2`, creating singleton sets for values
+    /// not yet seen. Returns whether two distinct sets were merged.
+    pub fn union(&mut self, v1: &V, v2: &V) -> bool {
+        let (index1, _) = self.core.insert(*v1, ());
+        let root1 = self.find_root(index1);
+        let (index2, _) = self.core.insert(*v2, ());
+        let root2 = self.find_root(index2);
+        self.core.link(root1, root2).is_some()
+    }
+
+    /// The representative value of the set containing `v`, or `None` if `v` has
+    /// never been added.
+    pub fn find(&mut self, v: &V) -> Option<V> {
+        let root = self.find_root(self.core.index(v)?);
+        Some(self.core.nodes[root].value)
+    }
+
+    pub fn same_set(&mut self, v1: &V, v2: &V) -> bool {
+        match (self.find(v1), self.find(v2)) {
+            (Some(r1), Some(r2)) => r1 == r2,
+            _ => false,
         }
     }
 
-    pub fn set_size(&mut self, v: &V) -> usize {
-        let root = self.find(*self.value2index.get(v).unwrap());
-        self.nodes[root].size
+    pub fn set_size(&mut self, v: &V) -> Option<usize> {
+        let root = self.find_root(self.core.index(v)?);
+        Some(self.core.nodes[root].size)
+    }
+
+    /// Every value in the same set as `v`, in insertion order.
+    pub fn members_of(&mut self, v: &V) -> Option<Vec<V>> {
+        let root = self.find_root(self.core.index(v)?);
+        let members = (0..self.core.nodes.len())
+            .filter(|i| self.find_root(*i) == root)
+            .collect::<Vec<_>>();
+        Some(members.iter().map(|i| self.core.nodes[*i].value).collect())
+    }
+
+    /// Every set, each listing its values in insertion order.
+    pub fn sets(&mut self) -> impl Iterator<Item = Vec<V>> {
+        let mut groups: BTreeMap<usize, Vec<V>> = BTreeMap::new();
+        for i in 0..self.core.nodes.len() {
+            let root = self.find_root(i);
+            groups
+                .entry(root)
+                .or_default()
+                .push(self.core.nodes[i].value);
+        }
+        groups.into_values()
     }
 
     pub fn all_sizes(&self) -> impl Iterator<Item = usize> {
-        self.roots.iter().map(|root| self.nodes[*root].size)
+        self.core.all_sizes()
     }
 
-    fn find(&mut self, index: usize) -> usize {
+    fn find_root(&mut self, index: usize) -> usize {
+        let nodes = &mut self.core.nodes;
         let mut root = index;
-        while self.nodes[root].parent != root {
-            root = self.nodes[root].parent;
+        while nodes[root].parent != root {
+            root = nodes[root].parent;
         }
         let mut current = index;
         while current != root {
             let temp = current;
-            current = self.nodes[temp].parent;
-            self.nodes[temp].parent = root;
+            current = nodes[temp].parent;
+            nodes[temp].parent = root;
         }
         root
     }
 }
 
-struct Node {
-    parent: usize,
-    size: usize,
+/// A `DisjointSetForest` whose operations can be undone. Union is by size with no
+/// path compression, so each change touches a constant number of nodes and can be
+/// reverted exactly; `find` costs O(log n).
+pub struct RollbackForest<V: DisjointSetKey> {
+    core: Core<V, ()>,
+    history: Vec<Change>,
+}
+
+/// A point in a `RollbackForest`'s history that it can be returned to.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Snapshot(usize);
+
+enum Change {
+    MakeSet,
+    Union { child: usize, parent: usize },
+}
+
+impl<V: DisjointSetKey> Default for RollbackForest<V> {
+    fn default() -> Self {
+        Self {
+            core: Core::default(),
+            history: vec![],
+        }
+    }
+}
+
+impl<V: DisjointSetKey> RollbackForest<V> {
+    /// Adds `value` as a singleton set; does nothing, and records nothing to roll
+    /// back, if it is already present.
+    pub fn make_set(&mut self, value: V) {
+        self.index_of(value);
+    }
+
+    fn index_of(&mut self, value: V) -> usize {
+        let (index, added) = self.core.insert(value, ());
+        if added {
+            self.history.push(Change::MakeSet);
+        }
+        index
+    }
+
+    pub fn num_roots(&self) -> usize {
+        self.core.roots.len()
+    }
+
+    /// Like `DisjointSetForest::union`, but recorded so that it can be rolled back.
+    pub fn union(&mut self, v1: &V, v2: &V) -> bool {
+        let index1 = self.index_of(*v1);
+        let root1 = self.find_root(index1);
+        let index2 = self.index_of(*v2);
+        let root2 = self.find_root(index2);
+        match self.core.link(root1, root2) {
+            Some((child, parent)) => {
+                self.history.push(Change::Union { child, parent });
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn find(&self, v: &V) -> Option<V> {
+        let root = self.find_root(self.core.index(v)?);
+        Some(self.core.nodes[root].value)
+    }
+
+    pub fn same_set(&self, v1: &V, v2: &V) -> bool {
+        match (self.find(v1), self.find(v2)) {
+            (Some(r1), Some(r2)) => r1 == r2,
+            _ => false,
+        }
+    }
+
+    pub fn set_size(&self, v: &V) -> Option<usize> {
+        let root = self.find_root(self.core.index(v)?);
+        Some(self.core.nodes[root].size)
+    }
+
+    pub fn all_sizes(&self) -> impl Iterator<Item = usize> {
+        self.core.all_sizes()
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot(self.history.len())
+    }
+
+    /// Undoes every `make_set` and merging `union` since `snapshot` was taken.
+    pub fn rollback_to(&mut self, snapshot: Snapshot) {
+        let core = &mut self.core;
+        while self.history.len() > snapshot.0 {
+            match self.history.pop().unwrap() {
+                Change::MakeSet => {
+                    let node = core.nodes.pop().unwrap();
+                    core.value2index.remove(&node.value);
+                    core.roots.remove(&core.nodes.len());
+                }
+                Change::Union { child, parent } => {
+                    core.nodes[child].parent = child;
+                    core.nodes[parent].size -= core.nodes[child].size;
+                    core.roots.insert(child);
+                }
+            }
+        }
+    }
+
+    fn find_root(&self, index: usize) -> usize {
+        let mut root = index;
+        while self.core.nodes[root].parent != root {
+            root = self.core.nodes[root].parent;
+        }
+        root
+    }
+}
+
+/// A disjoint-set forest in which each value also has an unknown numeric potential,
+/// and each union fixes the difference between two potentials. Each node stores its
+/// potential minus its parent's (zero for a root), so the difference between any two
+/// connected values can be recovered.
+pub struct WeightedForest<V: DisjointSetKey, N: NumType + Signed> {
+    core: Core<V, N>,
+}
+
+impl<V: DisjointSetKey, N: NumType + Signed> Default for WeightedForest<V, N> {
+    fn default() -> Self {
+        Self {
+            core: Core::default(),
+        }
+    }
+}
+
+impl<V: DisjointSetKey, N: NumType + Signed> WeightedForest<V, N> {
+    /// Adds `value` as a singleton set; does nothing if it is already present.
+    pub fn make_set(&mut self, value: V) {
+        self.core.insert(value, N::zero());
+    }
+
+    pub fn num_roots(&self) -> usize {
+        self.core.roots.len()
+    }
+
+    /// Records that the potential of `b` is `delta` more than that of `a`, creating
+    /// singleton sets for values not yet seen. Returns whether two distinct sets were
+    /// merged, or an error if `a` and `b` are already connected with a different
+    /// difference.
+    pub fn union_with_offset(&mut self, a: &V, b: &V, delta: N) -> anyhow::Result<bool> {
+        let (index_a, _) = self.core.insert(*a, N::zero());
+        let (root_a, offset_a) = self.find_root(index_a);
+        let (index_b, _) = self.core.insert(*b, N::zero());
+        let (root_b, offset_b) = self.find_root(index_b);
+        if root_a == root_b {
+            let existing = offset_b - offset_a;
+            if existing != delta {
+                bail!("{a:?} to {b:?} is already {existing}, not {delta}");
+            }
+            return Ok(false);
+        }
+        // Potential of root_b minus potential of root_a.
+        let root_delta = offset_a + delta - offset_b;
+        let (child, _) = self.core.link(root_a, root_b).unwrap();
+        self.core.nodes[child].data = if child == root_b {
+            root_delta
+        } else {
+            -root_delta
+        };
+        Ok(true)
+    }
+
+    /// The potential of `b` minus that of `a`, if they are connected.
+    pub fn difference(&mut self, a: &V, b: &V) -> Option<N> {
+        let (root_a, offset_a) = self.find_root(self.core.index(a)?);
+        let (root_b, offset_b) = self.find_root(self.core.index(b)?);
+        (root_a == root_b).then(|| offset_b - offset_a)
+    }
+
+    pub fn find(&mut self, v: &V) -> Option<V> {
+        let (root, _) = self.find_root(self.core.index(v)?);
+        Some(self.core.nodes[root].value)
+    }
+
+    pub fn same_set(&mut self, a: &V, b: &V) -> bool {
+        self.difference(a, b).is_some()
+    }
+
+    pub fn all_sizes(&self) -> impl Iterator<Item = usize> {
+        self.core.all_sizes()
+    }
+
+    /// Returns the root of `index` and the potential of `index` relative to it,
+    /// compressing the path along the way.
+    fn find_root(&mut self, index: usize) -> (usize, N) {
+        let nodes = &mut self.core.nodes;
+        let mut path = vec![];
+        let mut root = index;
+        while nodes[root].parent != root {
+            path.push(root);
+            root = nodes[root].parent;
+        }
+        let mut to_root = N::zero();
+        for node in path.iter().rev() {
+            to_root += nodes[*node].data;
+            nodes[*node].data = to_root;
+            nodes[*node].parent = root;
+        }
+        (root, nodes[index].data)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use itertools::Itertools;
 
-    use crate::union_find::DisjointSetForest;
+    use crate::union_find::{DisjointSetForest, RollbackForest, WeightedForest};
 
     #[test]
     fn test() {
@@ -96,8 +394,9 @@ mod tests {
         }
 
         for (n1, n2) in [(1, 2), (2, 3), (3, 4), (5, 6), (7, 8), (5, 7), (4, 9)] {
-            forest.union(&n1, &n2);
+            assert!(forest.union(&n1, &n2));
         }
+        assert!(!forest.union(&9, &1));
 
         for (n, size) in [
             (0, 1),
@@ -111,11 +410,92 @@ mod tests {
             (8, 4),
             (9, 5),
         ] {
-            assert_eq!(forest.set_size(&n), size);
+            assert_eq!(forest.set_size(&n), Some(size));
         }
 
         let mut root_sizes = forest.all_sizes().collect_vec();
         root_sizes.sort();
         assert_eq!(vec![1, 4, 5], root_sizes);
     }
+
+    #[test]
+    fn test_queries() {
+        let mut forest = DisjointSetForest::default();
+        for (n1, n2) in [('a', 'b'), ('c', 'd'), ('b', 'e')] {
+            forest.union(&n1, &n2);
+        }
+        forest.make_set('f');
+        forest.make_set('a');
+        assert_eq!(forest.num_roots(), 3);
+        assert_eq!(forest.find(&'a'), forest.find(&'e'));
+        assert_ne!(forest.find(&'a'), forest.find(&'c'));
+        assert_eq!(forest.find(&'z'), None);
+        assert!(forest.same_set(&'b', &'e'));
+        assert!(!forest.same_set(&'d', &'e'));
+        assert!(!forest.same_set(&'z', &'z'));
+        assert_eq!(forest.set_size(&'z'), None);
+        assert_eq!(forest.members_of(&'e'), Some(vec!['a', 'b', 'e']));
+        assert_eq!(forest.members_of(&'z'), None);
+
+        let mut sets = forest.sets().collect_vec();
+        sets.sort();
+        assert_eq!(sets, vec![vec!['a', 'b', 'e'], vec!['c', 'd'], vec!['f']]);
+    }
+
+    #[test]
+    fn test_rollback() {
+        let mut forest = RollbackForest::default();
+        for i in 0..6 {
+            forest.make_set(i);
+        }
+        forest.union(&0, &1);
+        forest.union(&2, &3);
+        let before = forest.snapshot();
+        assert!(forest.union(&1, &3));
+        assert!(!forest.union(&0, &2));
+        assert!(forest.union(&4, &7));
+        assert!(forest.same_set(&0, &3));
+        assert_eq!(forest.set_size(&7), Some(2));
+        assert_eq!(forest.num_roots(), 3);
+
+        forest.rollback_to(before);
+        assert!(!forest.same_set(&0, &3));
+        assert!(forest.same_set(&2, &3));
+        assert_eq!(forest.find(&7), None);
+        assert_eq!(forest.set_size(&4), Some(1));
+        assert_eq!(forest.num_roots(), 4);
+        let mut sizes = forest.all_sizes().collect_vec();
+        sizes.sort();
+        assert_eq!(sizes, vec![1, 1, 2, 2]);
+
+        let before = forest.snapshot();
+        forest.make_set(4);
+        assert_eq!(forest.snapshot(), before);
+        forest.make_set(8);
+        forest.rollback_to(before);
+        assert_eq!(forest.find(&4), Some(4));
+        assert_eq!(forest.find(&8), None);
+        assert_eq!(forest.num_roots(), 4);
+    }
+
+    #[test]
+    fn test_weighted() {
+        let mut forest = WeightedForest::default();
+        assert!(forest.union_with_offset(&'a', &'b', 3).unwrap());
+        assert!(forest.union_with_offset(&'c', &'d', -2).unwrap());
+        assert!(forest.union_with_offset(&'b', &'c', 10).unwrap());
+        assert_eq!(forest.difference(&'a', &'d'), Some(11));
+        assert_eq!(forest.difference(&'d', &'a'), Some(-11));
+        assert_eq!(forest.difference(&'c', &'c'), Some(0));
+        assert!(!forest.union_with_offset(&'d', &'b', -8).unwrap());
+        assert!(forest.union_with_offset(&'a', &'c', 12).is_err());
+        assert_eq!(forest.difference(&'a', &'c'), Some(13));
+
+        forest.make_set('e');
+        assert_eq!(forest.difference(&'a', &'e'), None);
+        assert_eq!(forest.difference(&'a', &'z'), None);
+        assert!(forest.same_set(&'d', &'a'));
+        assert_eq!(forest.find(&'a'), forest.find(&'d'));
+        assert_eq!(forest.num_roots(), 2);
+    }
 }
@@ -0,0 +1,256 @@
+use std::{cmp::min, fmt::Display};
+
+use pancurses::{Input, Window, curs_set, endwin, initscr, noecho};
+
+const DEFAULT_DELAY_MS: i32 = 200;
+const MIN_DELAY_MS: i32 = 10;
+const MAX_DELAY_MS: i32 = 5000;
+const HELP: &str = "space:pause  n/p:step  +/-:speed  arrows:scroll  g:first  q:quit";
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Command {
+    TogglePause,
+    Forward,
+    Back,
+    Faster,
+    Slower,
+    Scroll(isize, isize),
+    First,
+    Quit,
+}
+
+impl Command {
+    fn from_input(input: Input) -> Option<Self> {
+        match input {
+            Input::Character(' ') => Some(Self::TogglePause),
+            Input::Character('n') | Input::Character('.') => Some(Self::Forward),
+            Input::Character('p') | Input::Character(',') => Some(Self::Back),
+            Input::Character('+') | Input::Character('=') => Some(Self::Faster),
+            Input::Character('-') => Some(Self::Slower),
+            Input::Character('g') => Some(Self::First),
+            Input::Character('q') => Some(Self::Quit),
+            Input::KeyUp => Some(Self::Scroll(0, -1)),
+            Input::KeyDown => Some(Self::Scroll(0, 1)),
+            Input::KeyLeft => Some(Self::Scroll(-1, 0)),
+            Input::KeyRight => Some(Self::Scroll(1, 0)),
+            _ => None,
+        }
+    }
+}
+
+/// Playback state for a sequence of rendered frames, independent of the terminal.
+#[derive(Clone, Debug)]
+pub struct Viewer {
+    frames: Vec<Vec<String>>,
+    current: usize,
+    paused: bool,
+    delay_ms: i32,
+    scroll: (usize, usize),
+    done: bool,
+}
+
+impl Viewer {
+    /// Each frame is rendered through its `Display` implementation, so any
+    /// `GridWorld` with `CharDisplay` values or `InfiniteGrid` can be viewed.
+    pub fn new<D: Display, I: IntoIterator<Item = D>>(frames: I) -> Self {
+        Self {
+            frames: frames
+                .into_iter()
+                .map(|f| format!("{f}").lines().map(|s| s.to_string()).collect())
+                .collect(),
+            current: 0,
+            paused: false,
+            delay_ms: DEFAULT_DELAY_MS,
+            scroll: (0, 0),
+            done: false,
+        }
+    }
+
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    pub fn num_frames(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn delay_ms(&self) -> i32 {
+        self.delay_ms
+    }
+
+    pub fn scroll(&self) -> (usize, usize) {
+        self.scroll
+    }
+
+    pub fn done(&self) -> bool {
+        self.done
+    }
+
+    pub fn apply(&mut self, command: Command) {
+        match command {
+            Command::TogglePause => self.paused = !self.paused,
+            Command::Forward => {
+                self.paused = true;
+                self.advance();
+            }
+            Command::Back => {
+                self.paused = true;
+                self.current = self.current.saturating_sub(1);
+            }
+            Command::Faster => self.delay_ms = (self.delay_ms / 2).max(MIN_DELAY_MS),
+            Command::Slower => self.delay_ms = (self.delay_ms * 2).min(MAX_DELAY_MS),
+            Command::Scroll(dx, dy) => {
+                let (width, height) = self.extent();
+                self.scroll = (
+                    min(
+                        self.scroll.0.saturating_add_signed(dx),
+                        width.saturating_sub(1),
+                    ),
+                    min(
+                        self.scroll.1.saturating_add_signed(dy),
+                        height.saturating_sub(1),
+                    ),
+                );
+            }
+            Command::First => self.current = 0,
+            Command::Quit => self.done = true,
+        }
+    }
+
+    /// Called when no key arrives before the delay expires.
+    pub fn tick(&mut self) {
+        if !self.paused {
+            self.advance();
+            if self.current + 1 == self.frames.len() {
+                self.paused = true;
+            }
+        }
+    }
+
+    /// The widest line and the most lines of any frame.
+    fn extent(&self) -> (usize, usize) {
+        self.frames.iter().fold((0, 0), |(width, height), frame| {
+            let frame_width = frame.iter().map(|line| line.chars().count()).max();
+            (width.max(frame_width.unwrap_or(0)), height.max(frame.len()))
+        })
+    }
+
+    fn advance(&mut self) {
+        if self.current + 1 < self.frames.len() {
+            self.current += 1;
+        }
+    }
+
+    /// Animates the frames in the terminal until the user quits.
+    pub fn run(&mut self) {
+        let window = initscr();
+        window.keypad(true);
+        noecho();
+        curs_set(0);
+        while !self.done {
+            self.draw(&window);
+            window.timeout(self.delay_ms);
+            match window.getch() {
+                Some(input) => {
+                    if let Some(command) = Command::from_input(input) {
+                        self.apply(command);
+                    }
+                }
+                None => self.tick(),
+            }
+        }
+        endwin();
+    }
+
+    fn draw(&self, window: &Window) {
+        window.erase();
+        let (rows, cols) = window.get_max_yx();
+        let (rows, cols) = (rows.max(0), cols.max(0));
+        let view_rows = (rows - 2).max(0) as usize;
+        if let Some(frame) = self.frames.get(self.current) {
+            for (y, line) in frame.iter().skip(self.scroll.1).take(view_rows).enumerate() {
+                let visible = line
+                    .chars()
+                    .skip(self.scroll.0)
+                    .take(cols as usize)
+                    .collect::<String>();
+                window.mvaddstr(y as i32, 0, visible);
+            }
+        }
+        let status = format!(
+            "frame {}/{}  delay {} ms{}",
+            min(self.current + 1, self.frames.len()),
+            self.frames.len(),
+            self.delay_ms,
+            if self.paused { "  [paused]" } else { "" }
+        );
+        if rows >= 2 {
+            window.mvaddstr(rows - 2, 0, status);
+        }
+        if rows >= 1 {
+            window.mvaddstr(rows - 1, 0, HELP);
+        }
+        window.refresh();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::{GridCharWorld, InfiniteGrid};
+
+    use super::{Command, Viewer};
+
+    #[test]
+    fn test_playback() {
+        let frames = ["#.\n.#", "..\n.#", "..\n.."]
+            .iter()
+            .map(|s| s.parse::<GridCharWorld>().unwrap())
+            .collect::<Vec<_>>();
+        let mut viewer = Viewer::new(frames.iter());
+        assert_eq!(viewer.num_frames(), 3);
+        viewer.tick();
+        assert_eq!(viewer.current(), 1);
+        viewer.tick();
+        assert_eq!(viewer.current(), 2);
+        assert!(viewer.paused());
+        viewer.tick();
+        assert_eq!(viewer.current(), 2);
+
+        viewer.apply(Command::Back);
+        viewer.apply(Command::Back);
+        viewer.apply(Command::Back);
+        assert_eq!(viewer.current(), 0);
+        viewer.apply(Command::Forward);
+        assert_eq!(viewer.current(), 1);
+        viewer.apply(Command::TogglePause);
+        viewer.tick();
+        assert_eq!(viewer.current(), 2);
+
+        let delay = viewer.delay_ms();
+        viewer.apply(Command::Faster);
+        assert_eq!(viewer.delay_ms(), delay / 2);
+        viewer.apply(Command::Slower);
+        assert_eq!(viewer.delay_ms(), delay);
+
+        viewer.apply(Command::Scroll(-1, 2));
+        assert_eq!(viewer.scroll(), (0, 1));
+        viewer.apply(Command::Scroll(5, 0));
+        assert_eq!(viewer.scroll(), (1, 1));
+        viewer.apply(Command::First);
+        assert_eq!(viewer.current(), 0);
+        viewer.apply(Command::Quit);
+        assert!(viewer.done());
+    }
+
+    #[test]
+    fn test_infinite_frames() {
+        let mut grid = InfiniteGrid::default();
+        grid.add(-3, 5, 'x');
+        let viewer = Viewer::new([grid]);
+        assert_eq!(viewer.frames, vec![vec!["x".to_string()]]);
+    }
+}